@@ -1,55 +1,22 @@
 use common::error::PuzzleError;
+use common::look_and_say::ElementTable;
 
-pub fn solve_day10_puzzle_part1() -> Result<(), PuzzleError> {
+fn length_after(steps: u64) -> Result<u128, PuzzleError> {
     let input = std::fs::read_to_string("inputs/day10.txt")?;
-    let mut sequence = input.trim().to_string();
-    for _ in 0..40 {
-        let mut next_sequence = String::new();
-        let mut chars = sequence.chars().peekable();
-        while let Some(c) = chars.next() {
-            let mut count = 1;
-            while let Some(&next_c) = chars.peek() {
-                if next_c == c {
-                    count += 1;
-                    chars.next();
-                }
-                else {
-                    break;
-                }
-            }
-            next_sequence.push_str(&format!("{}{}", count, c));
-        }
-        sequence = next_sequence;
-    }
+    let table = ElementTable::new(input.trim())?;
+    table
+        .length_after(steps)
+        .ok_or(PuzzleError::Custom("Sequence length overflowed"))
+}
 
-    println!("Length after 40 iterations: {}", sequence.len());
+pub fn solve_day10_puzzle_part1() -> Result<(), PuzzleError> {
+    println!("Length after 40 iterations: {}", length_after(40)?);
 
     Ok(())
 }
 
 pub fn solve_day10_puzzle_part2() -> Result<(), PuzzleError> {
-    let input = std::fs::read_to_string("inputs/day10.txt")?;
-    let mut sequence = input.trim().to_string();
-    for _ in 0..50 {
-        let mut next_sequence = String::new();
-        let mut chars = sequence.chars().peekable();
-        while let Some(c) = chars.next() {
-            let mut count = 1;
-            while let Some(&next_c) = chars.peek() {
-                if next_c == c {
-                    count += 1;
-                    chars.next();
-                }
-                else {
-                    break;
-                }
-            }
-            next_sequence.push_str(&format!("{}{}", count, c));
-        }
-        sequence = next_sequence;
-    }
-
-    println!("Length after 40 iterations: {}", sequence.len());
+    println!("Length after 50 iterations: {}", length_after(50)?);
 
     Ok(())
 }
@@ -62,4 +29,4 @@ fn test_solve_day10_puzzle_part1() {
 #[test]
 fn test_solve_day10_puzzle_part2() {
     assert!(solve_day10_puzzle_part2().is_ok());
-}
\ No newline at end of file
+}
@@ -1,98 +1,31 @@
-use std::collections::HashSet;
-
-use once_cell::sync::Lazy;
-
 use common::error::PuzzleError;
+use common::password::Policy;
 
-static TRIGRAM_SET: Lazy<HashSet<&'static str>> = Lazy::new(|| {
-    let trigrams = [
-        "abc", "bcd", "cde", "def", "efg", "fgh", "ghi", "hij", "ijk", "jkl", "klm", "lmn", "mno", "nop", "opq", "pqr", "qrs", "rst", "stu", "tuv", "uvw", "vwx", "wxy", "xyz"
-    ];
-
-    trigrams.iter().cloned().collect()
-});
-
-pub fn solve_day11_puzzle_part1() -> Result<(), PuzzleError> {
+fn next_passwords(count: usize) -> Result<Vec<String>, PuzzleError> {
     let input = std::fs::read_to_string("inputs/day11.txt")?;
-    let mut new_password = increment_password(input.trim());
-    while !is_valid_password(&new_password) {
-        new_password = increment_password(&new_password);
+    let policy = Policy::santa();
+    let passwords = policy.passwords_after(input.trim())?.take(count).collect::<Vec<String>>();
+    if passwords.len() < count {
+        return Err(PuzzleError::Custom("Ran out of valid passwords"));
     }
 
-    println!("Next valid password: {}", new_password);
-
-    Ok(())
+    Ok(passwords)
 }
 
-pub fn solve_day11_puzzle_part2() -> Result<(), PuzzleError> {
-    let input = std::fs::read_to_string("inputs/day11.txt")?;
-    let mut new_password = increment_password(input.trim());
-    while !is_valid_password(&new_password) {
-        new_password = increment_password(&new_password);
-    }
-
-    new_password = increment_password(&new_password);
-    while !is_valid_password(&new_password) {
-        new_password = increment_password(&new_password);
-    }
+pub fn solve_day11_puzzle_part1() -> Result<(), PuzzleError> {
+    let passwords = next_passwords(1)?;
 
-    println!("Next valid password: {}", new_password);
+    println!("Next valid password: {}", passwords[0]);
 
     Ok(())
 }
 
-fn increment_password(password: &str) -> String {
-    let mut chars: Vec<char> = password.chars().collect();
-    let mut index = chars.len() - 1;
-    loop {
-        chars[index] = get_next_letter(chars[index]);
-        if chars[index] == 'a' {
-            if index == 0 {
-                break;
-            }
-            index -= 1;
-        }
-        else {
-            break;
-        }
-    }
-
-    chars.iter().collect()
-}
-
-fn get_next_letter(c: char) -> char {
-    match c {
-        'z' => 'a',
-        _ => ((c as u8) + 1) as char,
-    }
-}
-
-fn is_valid_password(password: &str) -> bool {
-    let chars = password.chars();
-    let mut has_increasing_straight = false;
-    let mut last_char = '\0';
-    let mut pairs_found = HashSet::new();
-    for (i, c) in chars.enumerate() {
-        match c {
-            'i' | 'o' | 'l' => return false,
-            _ => {}
-        }
-
-        if i < password.len() - 2 {
-            let trigram = &password[i..i + 3];
-            if TRIGRAM_SET.contains(trigram) {
-                has_increasing_straight = true;
-            }
-        }
-
-        if c == last_char {
-            pairs_found.insert(c);
-        }
+pub fn solve_day11_puzzle_part2() -> Result<(), PuzzleError> {
+    let passwords = next_passwords(2)?;
 
-        last_char = c;
-    }
+    println!("Next valid password: {}", passwords[1]);
 
-    has_increasing_straight && pairs_found.len() >= 2
+    Ok(())
 }
 
 #[test]
@@ -103,4 +36,4 @@ fn test_solve_day11_puzzle_part1() {
 #[test]
 fn test_solve_day11_puzzle_part2() {
     assert!(solve_day11_puzzle_part2().is_ok());
-}
\ No newline at end of file
+}
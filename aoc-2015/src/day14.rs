@@ -1,112 +1,33 @@
 use common::error::PuzzleError;
+use common::race::Race;
 
-pub fn solve_day14_puzzle_part1() -> Result<(), PuzzleError> {
-    const MAX_TIME: i32 = 2503;
+const RACE_DURATION: u64 = 2503;
 
+fn load_race() -> Result<Race, PuzzleError> {
     let input = std::fs::read_to_string("inputs/day14.txt")?;
-    let lines = input.lines();
-    let mut max_distance = 0;
-    for line in lines {
-        let parts = line.split_whitespace().collect::<Vec<_>>();
-        let speed = parts[3].parse::<i32>().unwrap();
-        let fly_time = parts[6].parse::<i32>().unwrap();
-        let rest_time = parts[13].parse::<i32>().unwrap();
-        let mut time_remaining = MAX_TIME;
-        let mut distance = 0;
-        while time_remaining > 0 {
-            if time_remaining >= fly_time {
-                distance += speed * fly_time;
-                time_remaining -= fly_time + rest_time;
-            }
-            else {
-                distance += speed * time_remaining;
-                break;
-            }
-        }
-
-        if distance > max_distance {
-            max_distance = distance;
-        }
-    }
-
-    println!("Distance of winning reindeer: {}", max_distance);
-    
-    Ok(())
+    Ok(Race::parse(&input)?)
 }
 
-#[derive(Debug)]
-struct Reigndeer {
-    speed: i32,
-    fly_time: i32,
-    rest_time: i32,
-    distance: i32,
-    time_remaining_in_state: i32,
-    flying: bool,
-    points: i32,
-}
+pub fn solve_day14_puzzle_part1() -> Result<(), PuzzleError> {
+    let race = load_race()?;
+    let max_distance = race.distances_at(RACE_DURATION).into_iter().max().unwrap_or(0);
 
-impl Reigndeer {
-    fn new(speed: i32, fly_time: i32, rest_time: i32) -> Self {
-        Self {
-            speed,
-            fly_time,
-            rest_time,
-            distance: 0,
-            time_remaining_in_state: fly_time,
-            flying: true,
-            points: 0,
-        }
-    }
+    println!("Distance of winning reindeer: {}", max_distance);
 
-    fn tick(&mut self) {
-        if self.flying {
-            self.distance += self.speed;
-        }
-        self.time_remaining_in_state -= 1;
-        if self.time_remaining_in_state == 0 {
-            if self.flying {
-                self.flying = false;
-                self.time_remaining_in_state = self.rest_time;
-            }
-            else {
-                self.flying = true;
-                self.time_remaining_in_state = self.fly_time;
-            }
-        }
-    }
+    Ok(())
 }
 
 pub fn solve_day14_puzzle_part2() -> Result<(), PuzzleError> {
-    const MAX_TIME: i32 = 2503;
+    let race = load_race()?;
+    let scoreboard = race.scoreboard(RACE_DURATION);
+    let winners = scoreboard
+        .winners()
+        .into_iter()
+        .map(|i| race.reindeer()[i].name.as_str())
+        .collect::<Vec<&str>>();
 
-    let input = std::fs::read_to_string("inputs/day14.txt")?;
-    let lines = input.lines();
-    let mut reindeers = Vec::new();
-    for line in lines {
-        let parts = line.split_whitespace().collect::<Vec<_>>();
-        let speed = parts[3].parse::<i32>().unwrap();
-        let fly_time = parts[6].parse::<i32>().unwrap();
-        let rest_time = parts[13].parse::<i32>().unwrap();
-        let reigndeer = Reigndeer::new(speed, fly_time, rest_time);
-        reindeers.push(reigndeer);
-    }
-
-    for _ in 0..MAX_TIME {
-        for reindeer in reindeers.iter_mut() {
-            reindeer.tick();
-        }
-        
-        let max_distance = reindeers.iter().map(|r| r.distance).max().unwrap();
-        for reindeer in reindeers.iter_mut() {
-            if reindeer.distance == max_distance {
-                reindeer.points += 1;
-            }
-        }
-    }
+    println!("Points of winning reindeer: {} ({})", scoreboard.best(), winners.join(", "));
 
-    let max_points = reindeers.iter().map(|r| r.points).max().unwrap();
-    println!("Points of winning reindeer: {}", max_points);
-    
     Ok(())
 }
 
@@ -118,4 +39,4 @@ fn test_day14_part1() {
 #[test]
 fn test_day14_part2() {
     assert!(solve_day14_puzzle_part2().is_ok());
-}
\ No newline at end of file
+}
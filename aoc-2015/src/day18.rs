@@ -1,202 +1,52 @@
+use common::automaton::{Automaton, Edges, Grid, Rule};
 use common::error::PuzzleError;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum LightState {
-    Off,
-    On,
-}
-
-pub fn solve_day18_puzzle_part1() -> Result<(), PuzzleError> {
-    const NUM_STEPS: usize = 100;
-    const GRID_SIZE: usize = 100;
+const NUM_STEPS: u64 = 100;
+const LONG_RUN_STEPS: u64 = 1_000_000_000;
+const CYCLE_SEARCH_LIMIT: u64 = 1_000_000;
 
+fn load_lights() -> Result<Automaton, PuzzleError> {
     let input = std::fs::read_to_string("inputs/day18.txt")?;
-    let lines = input.lines();
-    let mut current_grid = [[LightState::Off; GRID_SIZE]; GRID_SIZE];
-    let mut new_grid = [[LightState::Off; GRID_SIZE]; GRID_SIZE];
-    for (r, line) in lines.enumerate() {
-        for (c, ch) in line.chars().enumerate() {
-            current_grid[r][c] = match ch {
-                '#' => LightState::On,
-                '.' => LightState::Off,
-                _ => return Err(PuzzleError::Custom("Invalid character in input")),
-            };
-        }
-    }
+    let grid = Grid::parse(&input)?;
 
-    for _ in 0..NUM_STEPS {
-        for r in 0..GRID_SIZE {
-            for c in 0..GRID_SIZE {
-                let mut on_neighbors = 0;
-                if r > 0 {
-                    if c > 0 && current_grid[r - 1][c - 1] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                    if current_grid[r - 1][c] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                    if c < GRID_SIZE - 1 && current_grid[r - 1][c + 1] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                }
-
-                if c > 0 && current_grid[r][c - 1] == LightState::On {
-                    on_neighbors += 1;
-                }
-
-                if c < GRID_SIZE - 1 && current_grid[r][c + 1] == LightState::On {
-                    on_neighbors += 1;
-                }
-
-                if r < GRID_SIZE - 1 {
-                    if c > 0 && current_grid[r + 1][c - 1] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                    if current_grid[r + 1][c] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                    if c < GRID_SIZE - 1 && current_grid[r + 1][c + 1] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                }
-                match current_grid[r][c] {
-                    LightState::Off => {
-                        if on_neighbors == 3 {
-                            new_grid[r][c] = LightState::On;
-                        }
-                        else {
-                            new_grid[r][c] = LightState::Off;
-                        }
-                    },
-                    LightState::On => {
-                        if on_neighbors == 2 || on_neighbors == 3 {
-                            new_grid[r][c] = LightState::On;
-                        }
-                        else {
-                            new_grid[r][c] = LightState::Off;
-                        }
-                    },
-                }
-            }
-        }
-
-        std::mem::swap(&mut current_grid, &mut new_grid);
-    }
+    Ok(Automaton::new(grid, Rule::parse("B3/S23")?, Edges::Bounded))
+}
 
-    let mut on_count = 0;
-    for r in 0..GRID_SIZE {
-        for c in 0..GRID_SIZE {
-            if current_grid[r][c] == LightState::On {
-                on_count += 1;
-            }
-        }
-    }
+pub fn solve_day18_puzzle_part1() -> Result<(), PuzzleError> {
+    let mut lights = load_lights()?;
+    lights.run(NUM_STEPS);
 
-    println!("Number of lights on: {}", on_count);
+    println!("Number of lights on: {}", lights.grid().alive());
 
     Ok(())
 }
 
 pub fn solve_day18_puzzle_part2() -> Result<(), PuzzleError> {
-    const NUM_STEPS: usize = 100;
-    const GRID_SIZE: usize = 100;
+    let mut lights = load_lights()?;
+    lights.fix_corners(true)?;
+    lights.run(NUM_STEPS);
 
-    let input = std::fs::read_to_string("inputs/day18.txt")?;
-    let lines = input.lines();
-    let mut current_grid = [[LightState::Off; GRID_SIZE]; GRID_SIZE];
-    let mut new_grid = [[LightState::Off; GRID_SIZE]; GRID_SIZE];
-    for (r, line) in lines.enumerate() {
-        for (c, ch) in line.chars().enumerate() {
-            current_grid[r][c] = match ch {
-                '#' => LightState::On,
-                '.' => LightState::Off,
-                _ => return Err(PuzzleError::Custom("Invalid character in input")),
-            };
-        }
-    }
-
-    // Ensure corners are always on
-    current_grid[0][0] = LightState::On;
-    current_grid[0][GRID_SIZE - 1] = LightState::On;
-    current_grid[GRID_SIZE - 1][0] = LightState::On;
-    current_grid[GRID_SIZE - 1][GRID_SIZE - 1] = LightState::On;
-
-    for _ in 0..NUM_STEPS {
-        for r in 0..GRID_SIZE {
-            for c in 0..GRID_SIZE {
-                let mut on_neighbors = 0;
-                if r > 0 {
-                    if c > 0 && current_grid[r - 1][c - 1] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                    if current_grid[r - 1][c] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                    if c < GRID_SIZE - 1 && current_grid[r - 1][c + 1] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                }
-
-                if c > 0 && current_grid[r][c - 1] == LightState::On {
-                    on_neighbors += 1;
-                }
+    println!("Number of lights on: {}", lights.grid().alive());
 
-                if c < GRID_SIZE - 1 && current_grid[r][c + 1] == LightState::On {
-                    on_neighbors += 1;
-                }
+    Ok(())
+}
 
-                if r < GRID_SIZE - 1 {
-                    if c > 0 && current_grid[r + 1][c - 1] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                    if current_grid[r + 1][c] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                    if c < GRID_SIZE - 1 && current_grid[r + 1][c + 1] == LightState::On {
-                        on_neighbors += 1;
-                    }
-                }
-                match current_grid[r][c] {
-                    LightState::Off => {
-                        if on_neighbors == 3 {
-                            new_grid[r][c] = LightState::On;
-                        }
-                        else {
-                            new_grid[r][c] = LightState::Off;
-                        }
-                    },
-                    LightState::On => {
-                        if on_neighbors == 2 || on_neighbors == 3 {
-                            new_grid[r][c] = LightState::On;
-                        }
-                        else {
-                            new_grid[r][c] = LightState::Off;
-                        }
-                    },
-                }
-            }
+// Where both versions of the grid settle, and how many lights are on after
+// a billion steps
+pub fn solve_day18_puzzle_long_run() -> Result<(), PuzzleError> {
+    for stuck_corners in [false, true] {
+        let mut lights = load_lights()?;
+        if stuck_corners {
+            lights.fix_corners(true)?;
         }
+        let cycle = lights
+            .fast_forward(LONG_RUN_STEPS, CYCLE_SEARCH_LIMIT)
+            .ok_or(PuzzleError::Custom("The lights do not repeat within the search limit"))?;
 
-        // Ensure corners are always on
-        new_grid[0][0] = LightState::On;
-        new_grid[0][GRID_SIZE - 1] = LightState::On;
-        new_grid[GRID_SIZE - 1][0] = LightState::On;
-        new_grid[GRID_SIZE - 1][GRID_SIZE - 1] = LightState::On;
-
-        std::mem::swap(&mut current_grid, &mut new_grid);
-    }
-
-    let mut on_count = 0;
-    for r in 0..GRID_SIZE {
-        for c in 0..GRID_SIZE {
-            if current_grid[r][c] == LightState::On {
-                on_count += 1;
-            }
-        }
+        let label = if stuck_corners { "Stuck corners" } else { "Normal" };
+        println!("{}: {}, {} lights on after {} steps", label, cycle, lights.grid().alive(), lights.generation());
     }
 
-    println!("Number of lights on: {}", on_count);
-
     Ok(())
 }
 
@@ -210,4 +60,10 @@ fn test_day18_part1() {
 fn test_day18_part2() {
     let result = solve_day18_puzzle_part2();
     assert!(result.is_ok());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_day18_long_run() {
+    let result = solve_day18_puzzle_long_run();
+    assert!(result.is_ok());
+}
@@ -1,33 +1,26 @@
 use common::error::PuzzleError;
+use common::mining::Miner;
 
 pub fn solve_day4_puzzle_part1() -> Result<(), PuzzleError> {
     let input = std::fs::read_to_string("inputs/day4.txt")?;
-    let secret_key = input.trim();
+    let miner = Miner::new(input.trim());
+    let nonce = miner.lowest_nonce(5).ok_or(PuzzleError::Custom("No nonce found"))?;
 
-    for i in 0.. {
-        let hash_input = format!("{}{}", secret_key, i);
-        let digest = md5::compute(hash_input);
-        if digest[0] == 0 && digest[1] == 0 && (digest[2] & 0xF0) == 0 {
-            println!("Lowest number for five leading zeroes: {}", i);
-            break;
-        }
-    }
+    println!("Lowest number for five leading zeroes: {}", nonce);
 
     Ok(())
 }
 
 pub fn solve_day4_puzzle_part2() -> Result<(), PuzzleError> {
     let input = std::fs::read_to_string("inputs/day4.txt")?;
-    let secret_key = input.trim();
+    let miner = Miner::new(input.trim());
+    let nonces = miner.lowest_nonces(&[5, 6], u64::MAX);
+    let (Some(five), Some(six)) = (nonces[0], nonces[1]) else {
+        return Err(PuzzleError::Custom("No nonce found"));
+    };
 
-    for i in 0.. {
-        let hash_input = format!("{}{}", secret_key, i);
-        let digest = md5::compute(hash_input);
-        if digest[0] == 0 && digest[1] == 0 && digest[2] == 0 {
-            println!("Lowest number for six leading zeroes: {}", i);
-            break;
-        }
-    }
+    println!("Lowest number for five leading zeroes: {}", five);
+    println!("Lowest number for six leading zeroes: {}", six);
 
     Ok(())
 }
@@ -40,4 +33,4 @@ pub fn test_solve_day4_puzzle_part1() -> Result<(), PuzzleError> {
 #[test]
 pub fn test_solve_day4_puzzle_part2() -> Result<(), PuzzleError> {
     solve_day4_puzzle_part2()
-}
\ No newline at end of file
+}
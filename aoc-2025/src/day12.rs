@@ -1,3 +1,4 @@
+use common::dlx::polyomino::{Board, Packing, Placement, Polyomino};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -10,7 +11,7 @@ pub enum PuzzleError {
 struct Present {
     _id: usize,
     area: usize,
-    _cells: Vec<(i32, i32)>, // (dx, dy) offsets
+    cells: Vec<(i32, i32)>, // (dx, dy) offsets
 }
 
 impl Present {
@@ -43,7 +44,11 @@ impl Present {
 
         let area = cells.len();
 
-        Present { _id: id, area, _cells: cells }
+        Present { _id: id, area, cells }
+    }
+
+    fn shape(&self) -> Polyomino {
+        Polyomino::new(&self.cells)
     }
 }
 
@@ -95,13 +100,22 @@ impl XMasTree {
             return false; // Definitely cannot fit
         }
 
-        // Undecided case
-        panic!("Undecided case in solve_part1_v2")
+        self.pack(presents).is_some()
+    }
+
+    // Exact cover over the region where cells may be left empty but every
+    // present has to be placed
+    fn pack(&self, presents: &[Present]) -> Option<Vec<Placement>> {
+        let mut packing = Packing::with_gaps(Board::new(self.width, self.height));
+        for (id, &count) in self.gifts.iter().enumerate() {
+            packing.add_piece(presents[id].shape(), count);
+        }
+
+        packing.solve()
     }
 }
 
-pub fn solve_day12_puzzle_part1() -> Result<(), PuzzleError> {
-    let input = std::fs::read_to_string("inputs/day12.txt")?;
+fn parse_input(input: &str) -> (Vec<Present>, Vec<XMasTree>) {
     let mut lines = input.lines();
     let mut shapes = Vec::new();
     let mut shape = Vec::new();
@@ -150,6 +164,13 @@ pub fn solve_day12_puzzle_part1() -> Result<(), PuzzleError> {
         xmas_trees.push(xmas_tree);
     }
 
+    (presents, xmas_trees)
+}
+
+pub fn solve_day12_puzzle_part1() -> Result<(), PuzzleError> {
+    let input = std::fs::read_to_string("inputs/day12.txt")?;
+    let (presents, xmas_trees) = parse_input(&input);
+
     let mut solvable_count = 0;
     for tree in &xmas_trees {
         let can_solve = tree.solve_part1(&presents);
@@ -169,3 +190,65 @@ pub fn solve_day12_puzzle_part1() -> Result<(), PuzzleError> {
 fn test_day12_part1() {
     assert!(solve_day12_puzzle_part1().is_ok());
 }
+
+#[cfg(test)]
+const EXAMPLE: &str = "0:
+###
+##.
+##.
+
+1:
+###
+##.
+.##
+
+2:
+.##
+###
+##.
+
+3:
+##.
+###
+##.
+
+4:
+###
+#..
+###
+
+5:
+###
+.#.
+###
+
+4x4: 0 0 0 0 2 0
+12x5: 1 0 1 0 2 2
+12x5: 1 0 1 0 3 2
+";
+
+#[test]
+fn test_day12_example_packing() {
+    let (presents, xmas_trees) = parse_input(EXAMPLE);
+    let results = xmas_trees
+        .iter()
+        .map(|tree| tree.solve_part1(&presents))
+        .collect::<Vec<bool>>();
+    assert_eq!(results, vec![true, true, false]);
+}
+
+#[test]
+fn test_day12_packing_places_every_present() {
+    let (presents, xmas_trees) = parse_input(EXAMPLE);
+    let tree = &xmas_trees[1];
+    let packing = tree.pack(&presents).unwrap();
+    assert_eq!(packing.len(), tree.gifts.iter().sum::<usize>());
+
+    let mut occupied = std::collections::HashSet::new();
+    for placement in &packing {
+        for &(x, y) in &placement.cells {
+            assert!(x >= 0 && (x as usize) < tree.width && y >= 0 && (y as usize) < tree.height);
+            assert!(occupied.insert((x, y)));
+        }
+    }
+}
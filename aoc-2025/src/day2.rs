@@ -6,6 +6,40 @@ pub enum PuzzleError {
     IoError(#[from] std::io::Error),
 }
 
+fn is_repeated_twice(id: i64) -> bool {
+    let digits = id.to_string().chars().collect::<Vec<char>>();
+    let midpoint = digits.len() / 2;
+    if digits.len() % 2 != 0 {
+        return false;
+    }
+
+    let first_half = &digits[0..midpoint];
+    let second_half = &digits[midpoint..];
+    first_half.iter().zip(second_half.iter()).all(|(a, b)| a == b)
+}
+
+fn is_repeated_block(id: i64) -> bool {
+    let digits = id.to_string().chars().collect::<Vec<char>>();
+    let len = digits.len();
+    let midpoint = digits.len() / 2;
+    for i in 1..=midpoint {
+        if len % i != 0 {
+            continue;
+        }
+
+        let chunk_size = i;
+        let mut chunks = digits.chunks(chunk_size);
+        let first = chunks.next().unwrap();
+        let are_all_same = chunks.all(|chunk| chunk == first);
+        if are_all_same {
+            //println!("{} is invalid because all chunks ({:?}) are the same.", id, first);
+            return true;
+        }
+    }
+
+    false
+}
+
 pub fn solve_day2_puzzle_part1() -> Result<(), PuzzleError> {
     let ids = std::fs::read_to_string("inputs/day2.txt")?;
     let ranges = ids.split(',');
@@ -18,17 +52,7 @@ pub fn solve_day2_puzzle_part1() -> Result<(), PuzzleError> {
         let end = end.parse::<i64>().unwrap();
         let mut partial_invalid_ids = Vec::new();
         for id in start..=end {
-            let digits = id.to_string().chars().collect::<Vec<char>>();
-            let midpoint = digits.len() / 2;
-            if digits.len() % 2 != 0 {
-                continue;
-            }
-
-            let first_half = &digits[0..midpoint];
-            let second_half = &digits[midpoint..];
-            let is_same = first_half.iter().zip(second_half.iter()).all(|(a, b)| a == b);
-
-            if is_same {
+            if is_repeated_twice(id) {
                 partial_invalid_ids.push(id);
             }
         }
@@ -76,23 +100,8 @@ pub fn solve_day2_puzzle_part2() -> Result<(), PuzzleError> {
         let end = end.parse::<i64>().unwrap();
         let mut partial_invalid_ids = Vec::new();
         for id in start..=end {
-            let digits = id.to_string().chars().collect::<Vec<char>>();
-            let len = digits.len();
-            let midpoint = digits.len() / 2;
-            for i in 1..=midpoint {
-                if len % i != 0 {
-                    continue;
-                }
-
-                let chunk_size = i;
-                let mut chunks = digits.chunks(chunk_size);
-                let first = chunks.next().unwrap();
-                let are_all_same = chunks.all(|chunk| chunk == first);
-                if are_all_same {
-                    //println!("{} is invalid because all chunks ({:?}) are the same.", id, first);
-                    partial_invalid_ids.push(id);
-                    break;
-                }
+            if is_repeated_block(id) {
+                partial_invalid_ids.push(id);
             }
         }
 
@@ -127,6 +136,168 @@ pub fn solve_day2_puzzle_part2() -> Result<(), PuzzleError> {
     Ok(())
 }
 
+// u64::MAX has 20 digits
+const MAX_DIGITS: u32 = 20;
+
+#[derive(Debug, Clone, Copy)]
+enum Repeats {
+    Exactly(u32),
+    AtLeast(u32),
+}
+
+impl Repeats {
+    fn allows(&self, repeats: u32) -> bool {
+        match self {
+            Repeats::Exactly(n) => repeats == *n,
+            Repeats::AtLeast(n) => repeats >= *n,
+        }
+    }
+}
+
+fn pow10(exp: u32) -> u128 {
+    10u128.pow(exp)
+}
+
+// Multiplying a block by this repeats it, e.g. 123 * 1001001 = 123123123
+fn repeat_multiplier(block_len: u32, repeats: u32) -> u128 {
+    (pow10(block_len * repeats) - 1) / (pow10(block_len) - 1)
+}
+
+// The blocks of `block_len` digits (no leading zero) whose repetition lies in
+// [lo, hi]
+fn block_range(lo: u64, hi: u64, block_len: u32, repeats: u32) -> Option<(u128, u128)> {
+    let multiplier = repeat_multiplier(block_len, repeats);
+    let first = (lo as u128).div_ceil(multiplier).max(pow10(block_len - 1));
+    let last = (hi as u128 / multiplier).min(pow10(block_len) - 1);
+    if first > last {
+        return None;
+    }
+
+    Some((first, last))
+}
+
+// Count and sum of the `digits`-digit numbers in [lo, hi] that repeat with the
+// given period. A period equal to `digits` counts every number of that length.
+fn periodic_totals(lo: u64, hi: u64, digits: u32, period: u32) -> (i128, i128) {
+    let repeats = digits / period;
+    match block_range(lo, hi, period, repeats) {
+        Some((first, last)) => {
+            let count = last - first + 1;
+            let block_sum = (first + last) * count / 2;
+            (count as i128, (block_sum * repeat_multiplier(period, repeats)) as i128)
+        }
+        None => (0, 0),
+    }
+}
+
+fn mobius(mut n: u32) -> i128 {
+    let mut result = 1;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0;
+            }
+            result = -result;
+        }
+        p += 1;
+    }
+
+    if n > 1 { -result } else { result }
+}
+
+fn divisors(n: u32) -> Vec<u32> {
+    (1..=n).filter(|&d| n.is_multiple_of(d)).collect()
+}
+
+// Count and sum of the IDs in [lo, hi] made of one digit block repeated an
+// allowed number of times, without visiting the IDs in between. Numbers with
+// several valid block lengths (e.g. 111111) are counted once by splitting each
+// digit length by primitive period with Mobius inversion.
+fn repeated_id_totals(lo: u64, hi: u64, repeats: Repeats) -> (u128, u128) {
+    let (mut count, mut sum) = (0i128, 0i128);
+    for digits in 2..=MAX_DIGITS {
+        let periods = divisors(digits)
+            .into_iter()
+            .filter(|&period| period < digits && repeats.allows(digits / period))
+            .collect::<Vec<u32>>();
+        if periods.is_empty() {
+            continue;
+        }
+
+        for primitive in divisors(digits) {
+            if !periods.iter().any(|period| period.is_multiple_of(primitive)) {
+                continue;
+            }
+
+            for period in divisors(primitive) {
+                let mu = mobius(primitive / period);
+                let (c, s) = periodic_totals(lo, hi, digits, period);
+                count += mu * c;
+                sum += mu * s;
+            }
+        }
+    }
+
+    (count as u128, sum as u128)
+}
+
+// The repeated-block IDs in [lo, hi] in ascending order, generated directly
+// from their blocks
+fn repeated_ids(lo: u64, hi: u64, repeats: Repeats) -> Vec<u64> {
+    let mut ids = Vec::new();
+    for digits in 2..=MAX_DIGITS {
+        let mut digit_ids = Vec::new();
+        for period in divisors(digits) {
+            if period == digits || !repeats.allows(digits / period) {
+                continue;
+            }
+
+            if let Some((first, last)) = block_range(lo, hi, period, digits / period) {
+                let multiplier = repeat_multiplier(period, digits / period);
+                digit_ids.extend((first..=last).map(|block| (block * multiplier) as u64));
+            }
+        }
+
+        digit_ids.sort_unstable();
+        digit_ids.dedup();
+        ids.extend(digit_ids);
+    }
+
+    ids
+}
+
+fn solve_day2_arithmetic(repeats: Repeats) -> Result<(), PuzzleError> {
+    let ids = std::fs::read_to_string("inputs/day2.txt")?;
+    let mut total = 0;
+    for range in ids.trim().split(',') {
+        let mut bounds = range.split('-');
+        let start = bounds.next().unwrap().parse::<u64>().unwrap();
+        let end = bounds.next().unwrap().parse::<u64>().unwrap();
+        let (count, sum) = repeated_id_totals(start, end, repeats);
+        if count <= 10 {
+            println!("{} has invalid IDs {:?}", range, repeated_ids(start, end, repeats));
+        }
+        else {
+            println!("{} has {} invalid IDs summing to {}", range, count, sum);
+        }
+        total += sum;
+    }
+
+    println!("Sum of invalid IDs: {}", total);
+
+    Ok(())
+}
+
+pub fn solve_day2_puzzle_part1_v2() -> Result<(), PuzzleError> {
+    solve_day2_arithmetic(Repeats::Exactly(2))
+}
+
+pub fn solve_day2_puzzle_part2_v2() -> Result<(), PuzzleError> {
+    solve_day2_arithmetic(Repeats::AtLeast(2))
+}
+
 #[test]
 fn test_solve_day2_puzzle_part1() {
     assert!(solve_day2_puzzle_part1().is_ok());
@@ -135,4 +306,50 @@ fn test_solve_day2_puzzle_part1() {
 #[test]
 fn test_solve_day2_puzzle_part2() {
     assert!(solve_day2_puzzle_part2().is_ok());
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_solve_day2_puzzle_part1_v2() {
+    assert!(solve_day2_puzzle_part1_v2().is_ok());
+}
+
+#[test]
+fn test_solve_day2_puzzle_part2_v2() {
+    assert!(solve_day2_puzzle_part2_v2().is_ok());
+}
+
+#[test]
+fn test_day2_arithmetic_matches_brute_force() {
+    for (lo, hi) in [(1, 200_000), (998, 1012), (1_188_511_880, 1_188_511_890), (2_121_212_118, 2_121_212_124)] {
+        let twice = (lo..=hi).filter(|&id| is_repeated_twice(id as i64)).collect::<Vec<u64>>();
+        let blocks = (lo..=hi).filter(|&id| is_repeated_block(id as i64)).collect::<Vec<u64>>();
+        assert_eq!(repeated_ids(lo, hi, Repeats::Exactly(2)), twice);
+        assert_eq!(repeated_ids(lo, hi, Repeats::AtLeast(2)), blocks);
+
+        let (count, sum) = repeated_id_totals(lo, hi, Repeats::Exactly(2));
+        assert_eq!((count as usize, sum as u64), (twice.len(), twice.iter().sum::<u64>()));
+        let (count, sum) = repeated_id_totals(lo, hi, Repeats::AtLeast(2));
+        assert_eq!((count as usize, sum as u64), (blocks.len(), blocks.iter().sum::<u64>()));
+    }
+}
+
+#[test]
+fn test_day2_arithmetic_example() {
+    let example = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
+    let (mut part1, mut part2) = (0, 0);
+    for range in example.split(',') {
+        let (lo, hi) = range.split_once('-').unwrap();
+        let (lo, hi) = (lo.parse::<u64>().unwrap(), hi.parse::<u64>().unwrap());
+        part1 += repeated_id_totals(lo, hi, Repeats::Exactly(2)).1;
+        part2 += repeated_id_totals(lo, hi, Repeats::AtLeast(2)).1;
+    }
+
+    assert_eq!(part1, 1227775554);
+    assert_eq!(part2, 4174379265);
+
+    // Spans far too wide to iterate
+    let (count, _) = repeated_id_totals(1, 9_999_999_999, Repeats::Exactly(2));
+    assert_eq!(count, 9 + 90 + 900 + 9_000 + 90_000);
+    let (count, _) = repeated_id_totals(1, u64::MAX, Repeats::Exactly(2));
+    assert_eq!(count, 1_844_674_407);
+}
@@ -1,3 +1,6 @@
+use std::str::Lines;
+
+use common::interval::IntervalSet;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -6,11 +9,9 @@ pub enum PuzzleError {
     IoError(#[from] std::io::Error),
 }
 
-pub fn solve_day5_puzzle_part1() -> Result<(), PuzzleError> {
-    let input = std::fs::read_to_string("inputs/day5.txt")?;
-    let mut lines = input.lines();
-    let mut valid_items_ranges = Vec::new();
-    for line in &mut lines {
+fn parse_valid_ranges(lines: &mut Lines) -> IntervalSet<i64> {
+    let mut valid_items_ranges = IntervalSet::new();
+    for line in lines {
         if line.trim().is_empty() {
             break;
         }
@@ -18,14 +19,21 @@ pub fn solve_day5_puzzle_part1() -> Result<(), PuzzleError> {
         let mut parts = line.split('-');
         let lower = parts.next().unwrap().parse::<i64>().unwrap();
         let upper = parts.next().unwrap().parse::<i64>().unwrap();
-        valid_items_ranges.push((lower, upper));
+        valid_items_ranges.insert(lower..=upper);
     }
 
+    valid_items_ranges
+}
+
+pub fn solve_day5_puzzle_part1() -> Result<(), PuzzleError> {
+    let input = std::fs::read_to_string("inputs/day5.txt")?;
+    let mut lines = input.lines();
+    let valid_items_ranges = parse_valid_ranges(&mut lines);
+
     let mut valid_count = 0;
     for line in lines {
         let item = line.trim().parse::<i64>().unwrap();
-        let is_valid = valid_items_ranges.iter().any(|(lower, upper)| item >= *lower && item <= *upper);
-        if is_valid {
+        if valid_items_ranges.contains(item) {
             valid_count += 1;
         }
     }
@@ -38,51 +46,11 @@ pub fn solve_day5_puzzle_part1() -> Result<(), PuzzleError> {
 pub fn solve_day5_puzzle_part2() -> Result<(), PuzzleError> {
     let input = std::fs::read_to_string("inputs/day5.txt")?;
     let mut lines = input.lines();
-    let mut valid_items_ranges = Vec::new();
-    for line in &mut lines {
-        if line.trim().is_empty() {
-            break;
-        }
+    let valid_items_ranges = parse_valid_ranges(&mut lines);
 
-        let mut parts = line.split('-');
-        let lower = parts.next().unwrap().parse::<i64>().unwrap();
-        let upper = parts.next().unwrap().parse::<i64>().unwrap();
-        valid_items_ranges.push((lower, upper));
-    }
-
-    valid_items_ranges.sort_by_key(|&(l, _)| l);
-    let mut cleaned_ranges = Vec::new();
-    for (mut lower, mut upper) in valid_items_ranges {
-        let mut skip = false;
-        for (cl_lower, cl_upper) in &cleaned_ranges {
-            if lower > *cl_upper || upper < *cl_lower {
-                continue;
-            }
-
-            if lower >= *cl_lower && upper <= *cl_upper {
-                skip = true;
-                break;
-            }
-
-            if lower < *cl_lower && upper >= *cl_lower && upper <= *cl_upper {
-                upper = *cl_lower - 1;
-            }
-            else if lower <= *cl_upper && upper > *cl_upper {
-                lower = *cl_upper + 1;
-            }
-        }
-
-        if !skip {
-            cleaned_ranges.push((lower, upper));
-        }
-    }
+    //println!("Merged ranges: {:?}", valid_items_ranges.iter().collect::<Vec<_>>());
 
-    //println!("Cleaned ranges: {:?}", cleaned_ranges);
-
-    let mut valid_count = 0;
-    for (lower, upper) in &cleaned_ranges {
-        valid_count += upper - lower + 1;
-    }
+    let valid_count = valid_items_ranges.covered_len();
 
     println!("Number of valid items: {}", valid_count);
     
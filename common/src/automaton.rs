@@ -0,0 +1,439 @@
+use std::fmt;
+
+use thiserror::Error;
+
+pub mod cycle;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AutomatonError {
+    #[error("Invalid rule: {0}")]
+    Rule(String),
+    #[error("Invalid grid: {0}")]
+    Grid(&'static str),
+    #[error("Cell ({0}, {1}) is outside the grid")]
+    OutOfBounds(usize, usize),
+}
+
+// A Life-like rule: which live neighbor counts turn a dead cell on and which
+// keep a live one alive, as bit masks over the counts 0..=8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Rule {
+    // "B3/S23" or the older survival-first "23/3"
+    pub fn parse(rule: &str) -> Result<Self, AutomatonError> {
+        let error = || AutomatonError::Rule(rule.to_string());
+        let counts = |digits: &str| {
+            digits.chars().try_fold(0u16, |mask, digit| match digit.to_digit(10) {
+                Some(count) if count <= 8 => Ok(mask | 1 << count),
+                _ => Err(error()),
+            })
+        };
+
+        let (first, second) = rule.trim().split_once('/').ok_or_else(error)?;
+        let upper = |part: &str| part.to_ascii_uppercase();
+        let (first, second) = (upper(first), upper(second));
+        if let (Some(birth), Some(survival)) = (first.strip_prefix('B'), second.strip_prefix('S')) {
+            Ok(Rule {
+                birth: counts(birth)?,
+                survival: counts(survival)?,
+            })
+        }
+        else if let (Some(survival), Some(birth)) = (first.strip_prefix('S'), second.strip_prefix('B')) {
+            Ok(Rule {
+                birth: counts(birth)?,
+                survival: counts(survival)?,
+            })
+        }
+        else {
+            Ok(Rule {
+                birth: counts(&second)?,
+                survival: counts(&first)?,
+            })
+        }
+    }
+
+    pub fn life() -> Self {
+        Rule {
+            birth: 1 << 3,
+            survival: 1 << 2 | 1 << 3,
+        }
+    }
+
+    pub fn next_state(&self, alive: bool, neighbors: u32) -> bool {
+        let mask = if alive { self.survival } else { self.birth };
+        mask >> neighbors & 1 == 1
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |mask: u16| {
+            (0..=8)
+                .filter(|count| mask >> count & 1 == 1)
+                .map(|count| count.to_string())
+                .collect::<String>()
+        };
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survival))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edges {
+    Bounded,  // everything past the border is dead
+    Toroidal, // the border wraps around to the opposite side
+}
+
+// Cells packed 64 to a word, each row starting on a fresh word. Bits past
+// the width are always zero so equal grids compare and hash equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    words: usize, // per row
+    cells: Vec<u64>,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words = width.div_ceil(64);
+        Grid {
+            width,
+            height,
+            words,
+            cells: vec![0; words * height],
+        }
+    }
+
+    // One row per line, '#' for a live cell and '.' for a dead one
+    pub fn parse(input: &str) -> Result<Self, AutomatonError> {
+        let lines = input.lines().map(str::trim).filter(|line| !line.is_empty()).collect::<Vec<&str>>();
+        let width = lines.first().ok_or(AutomatonError::Grid("The grid is empty"))?.len();
+        let mut grid = Grid::new(width, lines.len());
+        for (row, line) in lines.iter().enumerate() {
+            if line.len() != width {
+                return Err(AutomatonError::Grid("Rows have different lengths"));
+            }
+            for (column, cell) in line.bytes().enumerate() {
+                match cell {
+                    b'#' => grid.set(row, column, true),
+                    b'.' => (),
+                    _ => return Err(AutomatonError::Grid("Cells must be '#' or '.'")),
+                }
+            }
+        }
+
+        Ok(grid)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> bool {
+        self.cells[row * self.words + column / 64] >> (column % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, row: usize, column: usize, alive: bool) {
+        let word = &mut self.cells[row * self.words + column / 64];
+        if alive {
+            *word |= 1 << (column % 64);
+        }
+        else {
+            *word &= !(1 << (column % 64));
+        }
+    }
+
+    pub fn alive(&self) -> usize {
+        self.cells.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    fn row(&self, row: usize) -> &[u64] {
+        &self.cells[row * self.words..(row + 1) * self.words]
+    }
+
+    // Mask of the bits in use in the last word of a row
+    fn tail_mask(&self) -> u64 {
+        match self.width % 64 {
+            0 => u64::MAX,
+            used => (1 << used) - 1,
+        }
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.height {
+            let line = (0..self.width).map(|column| if self.get(row, column) { '#' } else { '.' }).collect::<String>();
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Adds a one-bit value to every lane of a bit-sliced counter, plane i
+// holding bit i of each lane's count
+fn add_lanes(planes: &mut [u64; 4], bits: u64) {
+    let mut carry = bits;
+    for plane in planes {
+        let next = *plane & carry;
+        *plane ^= carry;
+        carry = next;
+    }
+}
+
+// Lanes whose count equals `count`
+fn lanes_equal(planes: &[u64; 4], count: u32) -> u64 {
+    planes
+        .iter()
+        .enumerate()
+        .fold(u64::MAX, |lanes, (bit, &plane)| lanes & if count >> bit & 1 == 1 { plane } else { !plane })
+}
+
+#[derive(Debug, Clone)]
+pub struct Automaton {
+    grid: Grid,
+    rule: Rule,
+    edges: Edges,
+    fixed: Grid,  // cells whose state never changes
+    values: Grid, // the state of each fixed cell
+    generation: u64,
+}
+
+impl Automaton {
+    pub fn new(grid: Grid, rule: Rule, edges: Edges) -> Self {
+        let (width, height) = (grid.width, grid.height);
+        Automaton {
+            grid,
+            rule,
+            edges,
+            fixed: Grid::new(width, height),
+            values: Grid::new(width, height),
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // Pins a cell to a state from now on, like the stuck lights in a corner
+    pub fn fix(&mut self, row: usize, column: usize, alive: bool) -> Result<(), AutomatonError> {
+        if row >= self.grid.height || column >= self.grid.width {
+            return Err(AutomatonError::OutOfBounds(row, column));
+        }
+
+        self.fixed.set(row, column, true);
+        self.values.set(row, column, alive);
+        self.grid.set(row, column, alive);
+        Ok(())
+    }
+
+    pub fn fix_corners(&mut self, alive: bool) -> Result<(), AutomatonError> {
+        let (last_row, last_column) = (self.grid.height.saturating_sub(1), self.grid.width.saturating_sub(1));
+        for (row, column) in [(0, 0), (0, last_column), (last_row, 0), (last_row, last_column)] {
+            self.fix(row, column, alive)?;
+        }
+
+        Ok(())
+    }
+
+    // The row's cells shifted so that bit c holds the cell at c - 1 (west)
+    // and c + 1 (east)
+    fn shifted(&self, row: &[u64]) -> (Vec<u64>, Vec<u64>) {
+        let words = row.len();
+        let mut west = vec![0; words];
+        let mut east = vec![0; words];
+        for i in 0..words {
+            west[i] = row[i] << 1 | if i > 0 { row[i - 1] >> 63 } else { 0 };
+            east[i] = row[i] >> 1 | if i + 1 < words { row[i + 1] << 63 } else { 0 };
+        }
+        west[words - 1] &= self.grid.tail_mask();
+
+        if self.edges == Edges::Toroidal {
+            let last = self.grid.width - 1;
+            west[0] |= row[last / 64] >> (last % 64) & 1;
+            east[last / 64] |= (row[0] & 1) << (last % 64);
+        }
+
+        (west, east)
+    }
+
+    // One generation, 64 cells at a time: the eight neighbor masks of a
+    // word are summed into bit-sliced counters and the rule picks the lanes
+    // to keep or bring to life
+    pub fn step(&mut self) {
+        let (width, height) = (self.grid.width, self.grid.height);
+        if width == 0 || height == 0 {
+            self.generation += 1;
+            return;
+        }
+
+        let words = self.grid.words;
+        let empty = vec![0; words];
+        let mut next = Grid::new(width, height);
+        let shifted_rows = (0..height).map(|row| self.shifted(self.grid.row(row))).collect::<Vec<_>>();
+        for row in 0..height {
+            let neighbor_row = |offset: isize| match (row as isize + offset, self.edges) {
+                (r, _) if (0..height as isize).contains(&r) => Some(r as usize),
+                (r, Edges::Toroidal) => Some(r.rem_euclid(height as isize) as usize),
+                (_, Edges::Bounded) => None,
+            };
+            let (above, below) = (neighbor_row(-1), neighbor_row(1));
+            let side = |r: Option<usize>| match r {
+                Some(r) => (self.grid.row(r), shifted_rows[r].0.as_slice(), shifted_rows[r].1.as_slice()),
+                None => (empty.as_slice(), empty.as_slice(), empty.as_slice()),
+            };
+            let (above, above_west, above_east) = side(above);
+            let (below, below_west, below_east) = side(below);
+            let (current, west, east) = side(Some(row));
+
+            for word in 0..words {
+                let mut planes = [0; 4];
+                for bits in [
+                    above_west[word],
+                    above[word],
+                    above_east[word],
+                    west[word],
+                    east[word],
+                    below_west[word],
+                    below[word],
+                    below_east[word],
+                ] {
+                    add_lanes(&mut planes, bits);
+                }
+
+                let alive = current[word];
+                let mut cells = 0;
+                for count in 0..=8 {
+                    let lanes = lanes_equal(&planes, count);
+                    if self.rule.birth >> count & 1 == 1 {
+                        cells |= lanes & !alive;
+                    }
+                    if self.rule.survival >> count & 1 == 1 {
+                        cells |= lanes & alive;
+                    }
+                }
+                if word == words - 1 {
+                    cells &= self.grid.tail_mask();
+                }
+
+                let index = row * words + word;
+                next.cells[index] = cells & !self.fixed.cells[index] | self.values.cells[index];
+            }
+        }
+
+        self.grid = next;
+        self.generation += 1;
+    }
+
+    pub fn run(&mut self, steps: u64) {
+        for _ in 0..steps {
+            self.step();
+        }
+    }
+}
+
+#[cfg(test)]
+fn naive_step(grid: &Grid, rule: Rule, edges: Edges) -> Grid {
+    let (width, height) = (grid.width() as isize, grid.height() as isize);
+    let mut next = Grid::new(grid.width(), grid.height());
+    for row in 0..height {
+        for column in 0..width {
+            let mut neighbors = 0;
+            for (dr, dc) in [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)] {
+                let (mut r, mut c) = (row + dr, column + dc);
+                if edges == Edges::Toroidal {
+                    (r, c) = (r.rem_euclid(height), c.rem_euclid(width));
+                }
+                if (0..height).contains(&r) && (0..width).contains(&c) && grid.get(r as usize, c as usize) {
+                    neighbors += 1;
+                }
+            }
+            let alive = rule.next_state(grid.get(row as usize, column as usize), neighbors);
+            next.set(row as usize, column as usize, alive);
+        }
+    }
+
+    next
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = ".#.#.#
+...##.
+#....#
+..#...
+#.#..#
+####..";
+
+#[test]
+fn test_rule_strings() {
+    assert_eq!(Rule::parse("B3/S23"), Ok(Rule::life()));
+    assert_eq!(Rule::parse("23/3"), Ok(Rule::life()));
+    assert_eq!(Rule::parse("s23/b3"), Ok(Rule::life()));
+    assert_eq!(Rule::parse("B36/S23").unwrap().to_string(), "B36/S23");
+    assert_eq!(Rule::parse("B/S012345678").unwrap().to_string(), "B/S012345678");
+    assert_eq!(Rule::parse("B39/S23"), Err(AutomatonError::Rule("B39/S23".to_string())));
+    assert!(Rule::parse("B3S23").is_err());
+}
+
+#[test]
+fn test_puzzle_examples() {
+    let grid = Grid::parse(EXAMPLE).unwrap();
+    let mut lights = Automaton::new(grid.clone(), Rule::life(), Edges::Bounded);
+    lights.run(4);
+    assert_eq!(lights.grid().to_string(), "......\n......\n..##..\n..##..\n......\n......\n");
+    assert_eq!(lights.generation(), 4);
+
+    let mut stuck = Automaton::new(grid, Rule::life(), Edges::Bounded);
+    stuck.fix_corners(true).unwrap();
+    stuck.run(5);
+    assert_eq!(stuck.grid().alive(), 17);
+    assert_eq!(stuck.fix(6, 0, true), Err(AutomatonError::OutOfBounds(6, 0)));
+    assert!(Grid::parse("#.\n#").is_err());
+}
+
+#[test]
+fn test_packed_steps_match_naive() {
+    let mut seed = 0x2015_0018_u64;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+
+    let rules = ["B3/S23", "B36/S23", "B2/S", "B1/S012345678", "B3678/S34678", "B0/S8"];
+    for (width, height) in [(1, 1), (2, 3), (63, 5), (64, 4), (65, 7), (130, 3), (3, 70)] {
+        for edges in [Edges::Bounded, Edges::Toroidal] {
+            let rule = Rule::parse(rules[random(rules.len() as u64) as usize]).unwrap();
+            let mut grid = Grid::new(width, height);
+            for row in 0..height {
+                for column in 0..width {
+                    grid.set(row, column, random(3) == 0);
+                }
+            }
+
+            let mut automaton = Automaton::new(grid.clone(), rule, edges);
+            for _ in 0..6 {
+                grid = naive_step(&grid, rule, edges);
+                automaton.step();
+                assert_eq!(automaton.grid(), &grid, "{rule} on {width}x{height} {edges:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use super::{Automaton, Grid};
+
+// Where an automaton's states start repeating: generation `pre_period` is
+// the first state seen again, `period` generations later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    pub pre_period: u64,
+    pub period: u64,
+}
+
+impl Cycle {
+    pub fn is_still_life(&self) -> bool {
+        self.period == 1
+    }
+
+    // The earliest generation whose state matches generation `generation`
+    pub fn reduce(&self, generation: u64) -> u64 {
+        if generation < self.pre_period {
+            generation
+        }
+        else {
+            self.pre_period + (generation - self.pre_period) % self.period
+        }
+    }
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "period {} after {} generations", self.period, self.pre_period)
+    }
+}
+
+// An automaton paired with a hash of its grid, so most comparisons between
+// states never look at the cells
+#[derive(Clone)]
+struct State {
+    automaton: Automaton,
+    hash: u64,
+}
+
+impl State {
+    fn new(automaton: Automaton) -> Self {
+        let hash = fingerprint(&automaton.grid);
+        State { automaton, hash }
+    }
+
+    fn step(&mut self) {
+        self.automaton.step();
+        self.hash = fingerprint(&self.automaton.grid);
+    }
+
+    fn same(&self, other: &State) -> bool {
+        self.hash == other.hash && self.automaton.grid == other.automaton.grid
+    }
+}
+
+fn fingerprint(grid: &Grid) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    grid.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Automaton {
+    // The cycle the automaton falls into from its current state, found with
+    // Brent's algorithm so only two states are held at a time. Gives up with
+    // None once the search would take more than `limit` generations.
+    pub fn find_cycle(&self, limit: u64) -> Option<Cycle> {
+        // Find the period by letting the hare run ahead of a tortoise that
+        // teleports to it at every power of two
+        let mut power = 1;
+        let mut period = 1;
+        let mut tortoise = State::new(self.clone());
+        let mut hare = State::new(self.clone());
+        hare.step();
+        let mut steps = 1;
+        while !tortoise.same(&hare) {
+            if steps >= limit {
+                return None;
+            }
+            if power == period {
+                tortoise = hare.clone();
+                power *= 2;
+                period = 0;
+            }
+            hare.step();
+            period += 1;
+            steps += 1;
+        }
+
+        // Then walk two states a period apart until they meet
+        let mut tortoise = State::new(self.clone());
+        let mut hare = State::new(self.clone());
+        for _ in 0..period {
+            hare.step();
+        }
+        let mut pre_period = 0;
+        while !tortoise.same(&hare) {
+            tortoise.step();
+            hare.step();
+            pre_period += 1;
+        }
+
+        Some(Cycle { pre_period, period })
+    }
+
+    // Advances `generations` generations, skipping whole periods when a cycle
+    // turns up within `limit` generations of searching, and returns it
+    pub fn fast_forward(&mut self, generations: u64, limit: u64) -> Option<Cycle> {
+        let target = self.generation + generations;
+        let cycle = self.find_cycle(limit.min(generations));
+        let steps = cycle.map_or(generations, |cycle| cycle.reduce(generations));
+        self.run(steps);
+        self.generation = target;
+        cycle
+    }
+}
+
+#[cfg(test)]
+use super::{Edges, Rule};
+
+#[cfg(test)]
+fn find_cycle_by_history(automaton: &Automaton) -> Cycle {
+    let mut seen = std::collections::HashMap::new();
+    let mut automaton = automaton.clone();
+    for generation in 0.. {
+        if let Some(&first) = seen.get(automaton.grid()) {
+            return Cycle {
+                pre_period: first,
+                period: generation - first,
+            };
+        }
+        seen.insert(automaton.grid().clone(), generation);
+        automaton.step();
+    }
+
+    unreachable!()
+}
+
+#[test]
+fn test_oscillators_and_still_lifes() {
+    let automaton = |pattern: &str, edges: Edges| Automaton::new(Grid::parse(pattern).unwrap(), Rule::life(), edges);
+
+    let blinker = automaton(".....\n..#..\n..#..\n..#..\n.....", Edges::Bounded);
+    assert_eq!(blinker.find_cycle(100), Some(Cycle { pre_period: 0, period: 2 }));
+    let block = automaton("....\n.##.\n.##.\n....", Edges::Bounded);
+    assert!(block.find_cycle(100).unwrap().is_still_life());
+
+    // A glider on an 8x8 torus is back where it started after 32 generations
+    let glider = automaton(
+        ".#......\n..#.....\n###.....\n........\n........\n........\n........\n........",
+        Edges::Toroidal,
+    );
+    assert_eq!(glider.find_cycle(100), Some(Cycle { pre_period: 0, period: 32 }));
+    assert_eq!(glider.find_cycle(31), None);
+
+    // Three in a corner become a block one generation later
+    let corner = automaton("##..\n#...\n....\n....", Edges::Bounded);
+    let cycle = corner.find_cycle(100).unwrap();
+    assert_eq!(cycle, Cycle { pre_period: 1, period: 1 });
+    assert_eq!(cycle.to_string(), "period 1 after 1 generations");
+}
+
+#[test]
+fn test_cycles_match_history() {
+    let mut seed = 0x2015_0050_u64;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+
+    for rule in ["B3/S23", "B36/S23", "B2/S", "B3/S012345678"] {
+        for edges in [Edges::Bounded, Edges::Toroidal] {
+            for _ in 0..8 {
+                let (width, height) = (3 + random(4) as usize, 3 + random(3) as usize);
+                let mut grid = Grid::new(width, height);
+                for row in 0..height {
+                    for column in 0..width {
+                        grid.set(row, column, random(2) == 0);
+                    }
+                }
+
+                let mut automaton = Automaton::new(grid, Rule::parse(rule).unwrap(), edges);
+                if random(2) == 0 {
+                    automaton.fix_corners(true).unwrap();
+                }
+                let cycle = automaton.find_cycle(u64::MAX).unwrap();
+                assert_eq!(cycle, find_cycle_by_history(&automaton), "{rule} {edges:?}\n{}", automaton.grid());
+
+                let generations = 1000 + random(500);
+                let mut expected = automaton.clone();
+                expected.run(generations);
+                assert_eq!(automaton.fast_forward(generations, u64::MAX), Some(cycle));
+                assert_eq!(automaton.grid(), expected.grid());
+                assert_eq!(automaton.generation(), generations);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fast_forward_a_billion_generations() {
+    let grid = Grid::parse(".....\n..#..\n..#..\n..#..\n.....").unwrap();
+    let mut blinker = Automaton::new(grid.clone(), Rule::life(), Edges::Bounded);
+    blinker.fast_forward(1_000_000_001, 1000);
+    assert_eq!(blinker.generation(), 1_000_000_001);
+    assert_eq!(blinker.grid().to_string(), ".....\n.....\n.###.\n.....\n.....\n");
+
+    // Without a cycle in reach it falls back to stepping
+    let mut short = Automaton::new(grid.clone(), Rule::life(), Edges::Bounded);
+    let mut expected = short.clone();
+    expected.run(3);
+    assert_eq!(short.fast_forward(3, 1), None);
+    assert_eq!(short.grid(), expected.grid());
+}
@@ -0,0 +1,791 @@
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::str::FromStr;
+
+use num_traits::{PrimInt, WrappingAdd};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CircuitError {
+    #[error("Invalid instruction: {0}")]
+    Parse(String),
+    #[error("Wire '{0}' is driven by more than one gate")]
+    MultipleDrivers(String),
+    #[error("Wire '{0}' has no driver")]
+    Undriven(String),
+    #[error("Unknown wire '{0}'")]
+    UnknownWire(String),
+    #[error("Combinational loop: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+// Unsigned word types a circuit can be built over; every wire carries one word
+pub trait Word: PrimInt + WrappingAdd + FromStr + Display + Debug + Hash + 'static {}
+
+impl Word for u8 {}
+impl Word for u16 {}
+impl Word for u32 {}
+impl Word for u64 {}
+
+fn bit_width<T: Word>() -> u32 {
+    T::zero().count_zeros()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand<T> {
+    Value(T),
+    Wire(String),
+}
+
+impl<T: Word> Operand<T> {
+    // A token made only of digits is a value and has to fit in a word,
+    // anything else names a wire
+    pub fn parse(token: &str) -> Result<Operand<T>, CircuitError> {
+        if !token.is_empty() && token.bytes().all(|byte| byte.is_ascii_digit()) {
+            token
+                .parse::<T>()
+                .map(Operand::Value)
+                .map_err(|_| CircuitError::Parse(token.to_string()))
+        }
+        else {
+            Ok(Operand::Wire(token.to_string()))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation<T> {
+    Const(T, String),                     // value, output wire
+    Assignment(Operand<T>, String),       // value/wire, output wire
+    And(Operand<T>, Operand<T>, String),  // left value/wire, right value/wire, output wire
+    Or(Operand<T>, Operand<T>, String),   // left value/wire, right value/wire, output wire
+    Xor(Operand<T>, Operand<T>, String),  // left value/wire, right value/wire, output wire
+    Nand(Operand<T>, Operand<T>, String), // left value/wire, right value/wire, output wire
+    Nor(Operand<T>, Operand<T>, String),  // left value/wire, right value/wire, output wire
+    Add(Operand<T>, Operand<T>, String),  // left value/wire, right value/wire, output wire (wrapping)
+    LShift(Operand<T>, u32, String),      // input value/wire, shift amount, output wire
+    RShift(Operand<T>, u32, String),      // input value/wire, shift amount, output wire
+    Not(Operand<T>, String),              // input value/wire, output wire
+}
+
+impl<T: Word> Operation<T> {
+    // Parses one line of a 2015 day7 style booklet, e.g. "x AND y -> z"
+    pub fn parse(line: &str) -> Result<Operation<T>, CircuitError> {
+        BookletParser
+            .parse_line(line)?
+            .ok_or_else(|| CircuitError::Parse(line.to_string()))
+    }
+
+    pub fn output(&self) -> &str {
+        match self {
+            Operation::Const(_, output)
+            | Operation::Assignment(_, output)
+            | Operation::And(_, _, output)
+            | Operation::Or(_, _, output)
+            | Operation::Xor(_, _, output)
+            | Operation::Nand(_, _, output)
+            | Operation::Nor(_, _, output)
+            | Operation::Add(_, _, output)
+            | Operation::LShift(_, _, output)
+            | Operation::RShift(_, _, output)
+            | Operation::Not(_, output) => output,
+        }
+    }
+
+    pub fn inputs(&self) -> Vec<&Operand<T>> {
+        match self {
+            Operation::Const(..) => vec![],
+            Operation::Assignment(input, _)
+            | Operation::LShift(input, _, _)
+            | Operation::RShift(input, _, _)
+            | Operation::Not(input, _) => vec![input],
+            Operation::And(left, right, _)
+            | Operation::Or(left, right, _)
+            | Operation::Xor(left, right, _)
+            | Operation::Nand(left, right, _)
+            | Operation::Nor(left, right, _)
+            | Operation::Add(left, right, _) => vec![left, right],
+        }
+    }
+
+    pub fn gate_name(&self) -> &'static str {
+        match self {
+            Operation::Const(..) => "CONST",
+            Operation::Assignment(..) => "ASSIGN",
+            Operation::And(..) => "AND",
+            Operation::Or(..) => "OR",
+            Operation::Xor(..) => "XOR",
+            Operation::Nand(..) => "NAND",
+            Operation::Nor(..) => "NOR",
+            Operation::Add(..) => "ADD",
+            Operation::LShift(..) => "LSHIFT",
+            Operation::RShift(..) => "RSHIFT",
+            Operation::Not(..) => "NOT",
+        }
+    }
+
+    // Graphviz node shape for wires driven by this kind of gate
+    pub fn dot_shape(&self) -> &'static str {
+        match self {
+            Operation::Const(..) => "box",
+            Operation::Assignment(..) => "ellipse",
+            Operation::And(..) => "house",
+            Operation::Or(..) => "invhouse",
+            Operation::Xor(..) => "hexagon",
+            Operation::Nand(..) => "trapezium",
+            Operation::Nor(..) => "invtrapezium",
+            Operation::Add(..) => "octagon",
+            Operation::LShift(..) => "larrow",
+            Operation::RShift(..) => "rarrow",
+            Operation::Not(..) => "invtriangle",
+        }
+    }
+
+    // `inputs` holds the values of `self.inputs()` in the same order. Shifting
+    // by the word width or more gives zero.
+    fn apply(&self, inputs: &[T]) -> T {
+        match self {
+            Operation::Const(value, _) => *value,
+            Operation::Assignment(..) => inputs[0],
+            Operation::And(..) => inputs[0] & inputs[1],
+            Operation::Or(..) => inputs[0] | inputs[1],
+            Operation::Xor(..) => inputs[0] ^ inputs[1],
+            Operation::Nand(..) => !(inputs[0] & inputs[1]),
+            Operation::Nor(..) => !(inputs[0] | inputs[1]),
+            Operation::Add(..) => inputs[0].wrapping_add(&inputs[1]),
+            Operation::LShift(_, shift, _) if *shift < bit_width::<T>() => inputs[0].unsigned_shl(*shift),
+            Operation::RShift(_, shift, _) if *shift < bit_width::<T>() => inputs[0].unsigned_shr(*shift),
+            Operation::LShift(..) | Operation::RShift(..) => T::zero(),
+            Operation::Not(..) => !inputs[0],
+        }
+    }
+}
+
+// Turns one line of some wiring format into a gate. Returning Ok(None) skips
+// lines that don't describe a gate, e.g. blank lines or headers.
+pub trait CircuitParser<T> {
+    fn parse_line(&self, line: &str) -> Result<Option<Operation<T>>, CircuitError>;
+}
+
+// The 2015 day7 booklet format, "x AND y -> z" or "NOT x -> h", extended with
+// XOR, NAND, NOR and ADD gates. A bare number on the left is a constant.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BookletParser;
+
+impl<T: Word> CircuitParser<T> for BookletParser {
+    fn parse_line(&self, line: &str) -> Result<Option<Operation<T>>, CircuitError> {
+        let invalid = || CircuitError::Parse(line.to_string());
+        let operand = |token: &str| Operand::parse(token).map_err(|_| invalid());
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        let operation = match parts.as_slice() {
+            [] => return Ok(None),
+            [input, "->", output] => match operand(input)? {
+                Operand::Value(value) => Operation::Const(value, output.to_string()),
+                wire => Operation::Assignment(wire, output.to_string()),
+            },
+            ["NOT", input, "->", output] => Operation::Not(operand(input)?, output.to_string()),
+            [left, op, right, "->", output] => {
+                let left = operand(left)?;
+                let output = output.to_string();
+                match *op {
+                    "AND" => Operation::And(left, operand(right)?, output),
+                    "OR" => Operation::Or(left, operand(right)?, output),
+                    "XOR" => Operation::Xor(left, operand(right)?, output),
+                    "NAND" => Operation::Nand(left, operand(right)?, output),
+                    "NOR" => Operation::Nor(left, operand(right)?, output),
+                    "ADD" => Operation::Add(left, operand(right)?, output),
+                    "LSHIFT" => Operation::LShift(left, right.parse().map_err(|_| invalid())?, output),
+                    "RSHIFT" => Operation::RShift(left, right.parse().map_err(|_| invalid())?, output),
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(invalid()),
+        };
+
+        Ok(Some(operation))
+    }
+}
+
+// The 2024 day24 format: initial wire values such as "x00: 1" followed by
+// gates such as "x00 AND y00 -> z00"
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GateListParser;
+
+impl<T: Word> CircuitParser<T> for GateListParser {
+    fn parse_line(&self, line: &str) -> Result<Option<Operation<T>>, CircuitError> {
+        let invalid = || CircuitError::Parse(line.to_string());
+        if let Some((wire, value)) = line.split_once(':') {
+            let value = value.trim().parse::<T>().map_err(|_| invalid())?;
+            return Ok(Some(Operation::Const(value, wire.trim().to_string())));
+        }
+
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        let operation = match parts.as_slice() {
+            [] => return Ok(None),
+            [left, op, right, "->", output] => {
+                let (left, right) = (Operand::Wire(left.to_string()), Operand::Wire(right.to_string()));
+                let output = output.to_string();
+                match *op {
+                    "AND" => Operation::And(left, right, output),
+                    "OR" => Operation::Or(left, right, output),
+                    "XOR" => Operation::Xor(left, right, output),
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(invalid()),
+        };
+
+        Ok(Some(operation))
+    }
+}
+
+// Gates adding the buses `left` and `right` bit by bit into `sum`, with
+// one-bit wires named like the 2024 day24 ones: x00 + y00 -> z00 and the
+// final carry on z{bits}. Each full adder is built from XOR, AND and OR gates
+// and its internal wires are named after the sum bit they feed, e.g. z03_carry.
+pub fn ripple_carry_adder<T: Word>(left: &str, right: &str, sum: &str, bits: usize) -> Vec<Operation<T>> {
+    let wire = |prefix: &str, bit: usize| Operand::Wire(format!("{}{:02}", prefix, bit));
+    let internal = |bit: usize, name: &str| format!("{}{:02}_{}", sum, bit, name);
+    let mut operations = Vec::with_capacity(5 * bits + 1);
+    let mut carry: Option<Operand<T>> = None;
+    for bit in 0..bits {
+        let (x, y) = (wire(left, bit), wire(right, bit));
+        let output = format!("{}{:02}", sum, bit);
+        let generate = internal(bit, "generate");
+        let next_carry = match carry {
+            None => {
+                operations.push(Operation::Xor(x.clone(), y.clone(), output));
+                operations.push(Operation::And(x, y, generate.clone()));
+                generate
+            }
+            Some(carry) => {
+                let half = internal(bit, "half");
+                let propagate = internal(bit, "propagate");
+                operations.push(Operation::Xor(x.clone(), y.clone(), half.clone()));
+                operations.push(Operation::Xor(Operand::Wire(half.clone()), carry.clone(), output));
+                operations.push(Operation::And(x, y, generate.clone()));
+                operations.push(Operation::And(Operand::Wire(half), carry, propagate.clone()));
+                let next_carry = internal(bit, "carry");
+                operations.push(Operation::Or(Operand::Wire(generate), Operand::Wire(propagate), next_carry.clone()));
+                next_carry
+            }
+        };
+        carry = Some(Operand::Wire(next_carry));
+    }
+
+    if let Some(carry) = carry {
+        operations.push(Operation::Assignment(carry, format!("{}{:02}", sum, bits)));
+    }
+
+    operations
+}
+
+// A wired circuit where every wire has at most one driving gate. Gates are
+// kept in topological order so evaluation visits each wire exactly once, and
+// any wire can be forced to a value to re-run the circuit with it cut off from
+// its driver.
+#[derive(Debug, Clone)]
+pub struct Netlist<T> {
+    names: Vec<String>,
+    ids: HashMap<String, usize>,
+    operations: Vec<Operation<T>>,
+    drivers: Vec<Option<usize>>,
+    inputs: Vec<Vec<Option<usize>>>, // per operation, wire id of each input or None for values
+    order: Vec<usize>,               // wire ids, dependencies first
+    overrides: HashMap<usize, T>,
+    values: Vec<Option<T>>,
+}
+
+impl<T: Word> Netlist<T> {
+    pub fn new(operations: Vec<Operation<T>>) -> Result<Self, CircuitError> {
+        let mut names = Vec::new();
+        let mut ids = HashMap::new();
+        let mut wire_id = |name: &str| -> usize {
+            *ids.entry(name.to_string()).or_insert_with(|| {
+                names.push(name.to_string());
+                names.len() - 1
+            })
+        };
+
+        let mut driver_of = Vec::new();
+        let mut inputs = Vec::with_capacity(operations.len());
+        for (index, operation) in operations.iter().enumerate() {
+            let output = wire_id(operation.output());
+            let operation_inputs = operation
+                .inputs()
+                .into_iter()
+                .map(|input| match input {
+                    Operand::Value(_) => None,
+                    Operand::Wire(name) => Some(wire_id(name)),
+                })
+                .collect::<Vec<Option<usize>>>();
+            inputs.push(operation_inputs);
+            driver_of.push((output, index));
+        }
+
+        let mut drivers = vec![None; names.len()];
+        for (output, index) in driver_of {
+            if drivers[output].replace(index).is_some() {
+                return Err(CircuitError::MultipleDrivers(names[output].clone()));
+            }
+        }
+
+        let mut netlist = Netlist {
+            values: vec![None; names.len()],
+            names,
+            ids,
+            operations,
+            drivers,
+            inputs,
+            order: Vec::new(),
+            overrides: HashMap::new(),
+        };
+        netlist.order = netlist.topological_order()?;
+
+        Ok(netlist)
+    }
+
+    pub fn parse(input: &str) -> Result<Self, CircuitError> {
+        Netlist::parse_with(input, &BookletParser)
+    }
+
+    pub fn parse_with<P: CircuitParser<T>>(input: &str, parser: &P) -> Result<Self, CircuitError> {
+        let mut operations = Vec::new();
+        for line in input.lines() {
+            if let Some(operation) = parser.parse_line(line)? {
+                operations.push(operation);
+            }
+        }
+
+        Netlist::new(operations)
+    }
+
+    pub fn operations(&self) -> &[Operation<T>] {
+        &self.operations
+    }
+
+    pub fn wires(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|name| name.as_str())
+    }
+
+    fn dependencies(&self, wire: usize) -> impl Iterator<Item = usize> + '_ {
+        self.drivers[wire]
+            .into_iter()
+            .flat_map(|op| self.inputs[op].iter().flatten().copied())
+    }
+
+    // Iterative depth-first search; meeting a wire that is still on the stack
+    // means the stack from that wire onwards is a loop
+    fn topological_order(&self) -> Result<Vec<usize>, CircuitError> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            Active,
+            Done,
+        }
+
+        let mut marks = vec![Mark::New; self.names.len()];
+        let mut order = Vec::with_capacity(self.names.len());
+        for root in 0..self.names.len() {
+            if marks[root] != Mark::New {
+                continue;
+            }
+
+            let mut stack = vec![(root, self.dependencies(root).collect::<Vec<usize>>())];
+            marks[root] = Mark::Active;
+            while let Some((wire, pending)) = stack.last_mut() {
+                let wire = *wire;
+                match pending.pop() {
+                    Some(next) => match marks[next] {
+                        Mark::New => {
+                            marks[next] = Mark::Active;
+                            stack.push((next, self.dependencies(next).collect()));
+                        }
+                        Mark::Active => {
+                            let start = stack.iter().position(|(w, _)| *w == next).unwrap();
+                            let mut cycle = stack[start..]
+                                .iter()
+                                .map(|(w, _)| self.names[*w].clone())
+                                .collect::<Vec<String>>();
+                            // The stack runs from consumer to input, signals flow the other way
+                            cycle.reverse();
+                            cycle.push(cycle[0].clone());
+                            return Err(CircuitError::Cycle(cycle));
+                        }
+                        Mark::Done => {}
+                    },
+                    None => {
+                        marks[wire] = Mark::Done;
+                        order.push(wire);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        Ok(order)
+    }
+
+    fn id(&self, wire: &str) -> Result<usize, CircuitError> {
+        self.ids
+            .get(wire)
+            .copied()
+            .ok_or_else(|| CircuitError::UnknownWire(wire.to_string()))
+    }
+
+    // Forces `wire` to `value` on the next evaluation, ignoring its driver
+    pub fn set_override(&mut self, wire: &str, value: T) -> Result<(), CircuitError> {
+        let id = self.id(wire)?;
+        self.overrides.insert(id, value);
+        Ok(())
+    }
+
+    pub fn clear_override(&mut self, wire: &str) -> Result<(), CircuitError> {
+        let id = self.id(wire)?;
+        self.overrides.remove(&id);
+        Ok(())
+    }
+
+    pub fn clear_overrides(&mut self) {
+        self.overrides.clear();
+    }
+
+    pub fn evaluate(&mut self) -> Result<(), CircuitError> {
+        self.values.iter_mut().for_each(|value| *value = None);
+        for &wire in &self.order {
+            let value = match (self.overrides.get(&wire), self.drivers[wire]) {
+                (Some(&value), _) => value,
+                (None, Some(op)) => {
+                    let operation = &self.operations[op];
+                    let mut input_values = Vec::with_capacity(2);
+                    for (operand, input) in operation.inputs().into_iter().zip(&self.inputs[op]) {
+                        match (operand, input) {
+                            (Operand::Value(value), _) => input_values.push(*value),
+                            (_, Some(input)) => match self.values[*input] {
+                                Some(value) => input_values.push(value),
+                                None => return Err(CircuitError::Undriven(self.names[*input].clone())),
+                            },
+                            (Operand::Wire(name), None) => return Err(CircuitError::UnknownWire(name.clone())),
+                        }
+                    }
+                    operation.apply(&input_values)
+                }
+                (None, None) => continue,
+            };
+
+            self.values[wire] = Some(value);
+        }
+
+        Ok(())
+    }
+
+    // The wire's value from the last evaluation
+    pub fn value(&self, wire: &str) -> Result<T, CircuitError> {
+        let id = self.id(wire)?;
+        self.values[id].ok_or_else(|| CircuitError::Undriven(wire.to_string()))
+    }
+
+    // Reads single-bit wires named `prefix` followed by a bit index (z00, z01,
+    // ...) as one little-endian number
+    pub fn bus_value(&self, prefix: &str) -> Result<u64, CircuitError> {
+        let mut result = 0;
+        for name in &self.names {
+            let Some(bit) = name.strip_prefix(prefix).and_then(|index| index.parse::<u32>().ok()) else {
+                continue;
+            };
+            if self.value(name)? != T::zero() && bit < 64 {
+                result |= 1 << bit;
+            }
+        }
+
+        Ok(result)
+    }
+
+    // Forces the single-bit wires named `prefix` followed by a bit index to the
+    // bits of `value`, the counterpart of `bus_value`
+    pub fn set_bus(&mut self, prefix: &str, value: u64) {
+        for (id, name) in self.names.iter().enumerate() {
+            let Some(bit) = name.strip_prefix(prefix).and_then(|index| index.parse::<u32>().ok()) else {
+                continue;
+            };
+            let set = bit < 64 && value >> bit & 1 == 1;
+            self.overrides.insert(id, if set { T::one() } else { T::zero() });
+        }
+    }
+
+    fn consumers(&self) -> Vec<Vec<usize>> {
+        let mut consumers = vec![Vec::new(); self.names.len()];
+        for wire in 0..self.names.len() {
+            for input in self.dependencies(wire) {
+                consumers[input].push(wire);
+            }
+        }
+
+        consumers
+    }
+
+    // Every wire reachable from `start` through `next`, excluding `start`,
+    // listed in evaluation order
+    fn cone(&self, start: usize, next: impl Fn(usize) -> Vec<usize>) -> Vec<&str> {
+        let mut seen = vec![false; self.names.len()];
+        let mut stack = next(start);
+        while let Some(wire) = stack.pop() {
+            if !seen[wire] {
+                seen[wire] = true;
+                stack.extend(next(wire));
+            }
+        }
+
+        self.order
+            .iter()
+            .filter(|&&wire| seen[wire] && wire != start)
+            .map(|&wire| self.names[wire].as_str())
+            .collect()
+    }
+
+    // All wires `wire` transitively depends on
+    pub fn fan_in(&self, wire: &str) -> Result<Vec<&str>, CircuitError> {
+        let id = self.id(wire)?;
+        Ok(self.cone(id, |w| self.dependencies(w).collect()))
+    }
+
+    // All wires that transitively depend on `wire`
+    pub fn fan_out(&self, wire: &str) -> Result<Vec<&str>, CircuitError> {
+        let id = self.id(wire)?;
+        let consumers = self.consumers();
+        Ok(self.cone(id, |w| consumers[w].clone()))
+    }
+
+    // Number of gates on the longest path from an undriven wire or constant to
+    // each wire. Plain wire-to-wire assignments are buffers and add no depth.
+    pub fn depths(&self) -> HashMap<&str, usize> {
+        let mut depths = vec![0; self.names.len()];
+        for &wire in &self.order {
+            if let Some(op) = self.drivers[wire] {
+                let deepest_input = self.dependencies(wire).map(|input| depths[input] + 1).max();
+                depths[wire] = match (&self.operations[op], deepest_input) {
+                    (_, None) => 0,
+                    (Operation::Assignment(..), Some(depth)) => depth - 1,
+                    (_, Some(depth)) => depth,
+                };
+            }
+        }
+
+        self.names
+            .iter()
+            .zip(depths)
+            .map(|(name, depth)| (name.as_str(), depth))
+            .collect()
+    }
+
+    pub fn depth(&self, wire: &str) -> Result<usize, CircuitError> {
+        self.id(wire)?;
+        Ok(self.depths()[wire])
+    }
+
+    // Graphviz digraph with one node per wire, shaped by the gate driving it.
+    // Constant operands are folded into the node label and undriven wires are
+    // drawn as diamonds.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph circuit {\n    rankdir=LR;\n");
+        for &wire in &self.order {
+            let name = &self.names[wire];
+            let (shape, label) = match self.drivers[wire] {
+                None => ("diamond", name.clone()),
+                Some(op) => {
+                    let operation = &self.operations[op];
+                    let constants = operation
+                        .inputs()
+                        .into_iter()
+                        .filter_map(|input| match input {
+                            Operand::Value(value) => Some(value.to_string()),
+                            Operand::Wire(_) => None,
+                        })
+                        .chain(match operation {
+                            Operation::Const(value, _) => Some(value.to_string()),
+                            Operation::LShift(_, shift, _) | Operation::RShift(_, shift, _) => Some(shift.to_string()),
+                            _ => None,
+                        })
+                        .collect::<Vec<String>>();
+                    let gate = match operation {
+                        Operation::Assignment(..) if constants.is_empty() => String::new(),
+                        Operation::Assignment(..) | Operation::Const(..) => format!("\\n= {}", constants.join(" ")),
+                        _ if constants.is_empty() => format!("\\n{}", operation.gate_name()),
+                        _ => format!("\\n{} {}", operation.gate_name(), constants.join(" ")),
+                    };
+                    (operation.dot_shape(), format!("{}{}", name, gate))
+                }
+            };
+            dot.push_str(&format!("    \"{}\" [shape={}, label=\"{}\"];\n", name, shape, label));
+        }
+
+        for &wire in &self.order {
+            for input in self.dependencies(wire) {
+                dot.push_str(&format!("    \"{}\" -> \"{}\";\n", self.names[input], self.names[wire]));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "123 -> x
+456 -> y
+x AND y -> d
+x OR y -> e
+x LSHIFT 2 -> f
+y RSHIFT 2 -> g
+NOT x -> h
+NOT y -> i
+";
+
+#[test]
+fn test_netlist_example() {
+    let mut netlist = Netlist::<u16>::parse(EXAMPLE).unwrap();
+    netlist.evaluate().unwrap();
+    let expected = [("d", 72), ("e", 507), ("f", 492), ("g", 114), ("h", 65412), ("i", 65079), ("x", 123), ("y", 456)];
+    for (wire, value) in expected {
+        assert_eq!(netlist.value(wire), Ok(value));
+    }
+}
+
+#[test]
+fn test_netlist_override_and_reevaluate() {
+    // Lines are out of order on purpose, the netlist sorts them
+    let mut netlist = Netlist::<u16>::parse("b -> a\nc AND 7 -> b\n14 -> c").unwrap();
+    netlist.evaluate().unwrap();
+    assert_eq!(netlist.value("a"), Ok(6));
+
+    netlist.set_override("b", 3).unwrap();
+    netlist.evaluate().unwrap();
+    assert_eq!(netlist.value("a"), Ok(3));
+
+    netlist.clear_overrides();
+    netlist.evaluate().unwrap();
+    assert_eq!(netlist.value("a"), Ok(6));
+    assert_eq!(netlist.set_override("z", 1), Err(CircuitError::UnknownWire("z".to_string())));
+}
+
+#[test]
+fn test_netlist_errors() {
+    let error = Netlist::<u16>::parse("a AND 1 -> b\nb -> c\nNOT c -> a\n5 -> d").unwrap_err();
+    assert_eq!(error.to_string(), "Combinational loop: c -> a -> b -> c");
+
+    let error = Netlist::<u16>::parse("1 -> a\n2 -> a").unwrap_err();
+    assert_eq!(error, CircuitError::MultipleDrivers("a".to_string()));
+
+    let mut netlist = Netlist::<u16>::parse("x OR 1 -> a").unwrap();
+    assert_eq!(netlist.evaluate(), Err(CircuitError::Undriven("x".to_string())));
+    netlist.set_override("x", 4).unwrap();
+    netlist.evaluate().unwrap();
+    assert_eq!(netlist.value("a"), Ok(5));
+
+    assert!(matches!(Operation::<u16>::parse("a XNOR b -> c"), Err(CircuitError::Parse(_))));
+
+    // Numbers have to fit in the word rather than naming a wire "300"
+    let error = Netlist::<u8>::parse("300 -> x\nx AND 1 -> y").unwrap_err();
+    assert_eq!(error, CircuitError::Parse("300 -> x".to_string()));
+    assert!(Netlist::<u8>::parse("a OR 256 -> b").is_err());
+    assert!(Netlist::<u16>::parse("300 -> x").is_ok());
+    assert_eq!(Operand::<u8>::parse("255"), Ok(Operand::Value(255)));
+    assert_eq!(Operand::<u8>::parse("x300"), Ok(Operand::Wire("x300".to_string())));
+    assert_eq!(Operand::<u8>::parse("300"), Err(CircuitError::Parse("300".to_string())));
+}
+
+#[test]
+fn test_netlist_cones_and_depth() {
+    let netlist = Netlist::<u16>::parse("b -> a\nc AND d -> b\nNOT e -> c\n3 -> d\nx LSHIFT 1 -> e\nc OR 1 -> f").unwrap();
+    fn sorted(mut wires: Vec<&str>) -> Vec<&str> {
+        wires.sort_unstable();
+        wires
+    }
+
+    assert_eq!(netlist.fan_in("a").map(sorted), Ok(vec!["b", "c", "d", "e", "x"]));
+    assert_eq!(netlist.fan_in("b").map(sorted), Ok(vec!["c", "d", "e", "x"]));
+    assert_eq!(netlist.fan_out("c").map(sorted), Ok(vec!["a", "b", "f"]));
+    assert_eq!(netlist.fan_out("a"), Ok(vec![]));
+
+    // Cones come back in evaluation order
+    let fan_in = netlist.fan_in("a").unwrap();
+    let position = |wire| fan_in.iter().position(|w| *w == wire).unwrap();
+    assert!(position("x") < position("e") && position("e") < position("c") && position("c") < position("b"));
+
+    let depths = netlist.depths();
+    assert_eq!(depths["x"], 0);
+    assert_eq!(depths["d"], 0);
+    assert_eq!(depths["e"], 1);
+    assert_eq!(depths["c"], 2);
+    assert_eq!(depths["b"], 3);
+    assert_eq!(depths["a"], 3);
+    assert_eq!(netlist.depth("f"), Ok(3));
+    assert!(netlist.depth("nope").is_err());
+}
+
+#[test]
+fn test_netlist_dot_export() {
+    let netlist = Netlist::<u16>::parse("x AND 7 -> b\nb -> a\n14 -> c\nc RSHIFT 2 -> d").unwrap();
+    let dot = netlist.to_dot();
+    assert!(dot.starts_with("digraph circuit {"));
+    assert!(dot.contains("\"x\" [shape=diamond, label=\"x\"];"));
+    assert!(dot.contains("\"b\" [shape=house, label=\"b\\nAND 7\"];"));
+    assert!(dot.contains("\"a\" [shape=ellipse, label=\"a\"];"));
+    assert!(dot.contains("\"c\" [shape=box, label=\"c\\n= 14\"];"));
+    assert!(dot.contains("\"d\" [shape=rarrow, label=\"d\\nRSHIFT 2\"];"));
+    assert!(dot.contains("\"x\" -> \"b\";"));
+    assert!(dot.contains("\"c\" -> \"d\";"));
+    assert_eq!(dot.matches("->").count(), 3);
+}
+
+#[test]
+fn test_netlist_word_widths_and_extra_gates() {
+    let booklet = "200 -> x\n100 -> y\nx XOR y -> a\nx NAND y -> b\nx NOR y -> c\nx ADD y -> d\nx LSHIFT 9 -> e\nx RSHIFT 3 -> f";
+    let mut narrow = Netlist::<u8>::parse(booklet).unwrap();
+    narrow.evaluate().unwrap();
+    assert_eq!(narrow.value("a"), Ok(200 ^ 100));
+    assert_eq!(narrow.value("b"), Ok(!(200u8 & 100)));
+    assert_eq!(narrow.value("c"), Ok(!(200u8 | 100)));
+    assert_eq!(narrow.value("d"), Ok(44));
+    assert_eq!(narrow.value("e"), Ok(0));
+    assert_eq!(narrow.value("f"), Ok(25));
+
+    let mut wide = Netlist::<u64>::parse(booklet).unwrap();
+    wide.evaluate().unwrap();
+    assert_eq!(wide.value("d"), Ok(300));
+    assert_eq!(wide.value("e"), Ok(200 << 9));
+    assert_eq!(wide.value("b"), Ok(!(200u64 & 100)));
+}
+
+#[test]
+fn test_netlist_gate_list_format() {
+    // Example from 2024 day24
+    let input = "x00: 1\nx01: 1\nx02: 1\ny00: 0\ny01: 1\ny02: 0\n\nx00 AND y00 -> z00\nx01 XOR y01 -> z01\nx02 OR y02 -> z02";
+    let mut netlist = Netlist::<u8>::parse_with(input, &GateListParser).unwrap();
+    netlist.evaluate().unwrap();
+    assert_eq!(netlist.bus_value("z"), Ok(0b100));
+    assert_eq!(netlist.bus_value("x"), Ok(0b111));
+    assert!(Netlist::<u8>::parse_with("x00 NOT y00 -> z00", &GateListParser).is_err());
+}
+
+#[test]
+fn test_ripple_carry_adder() {
+    let mut seed = 0x2015_0033_u64;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+
+    let mut adder = Netlist::<u8>::new(ripple_carry_adder("x", "y", "z", 12)).unwrap();
+    assert_eq!(adder.depth("z00"), Ok(1));
+    assert_eq!(adder.depth("z12"), Ok(23)); // the carry chain
+    assert_eq!(adder.fan_in("z01").map(|wires| wires.len()), Ok(6));
+    for _ in 0..100 {
+        let (x, y) = (random(1 << 12), random(1 << 12));
+        adder.set_bus("x", x);
+        adder.set_bus("y", y);
+        adder.evaluate().unwrap();
+        assert_eq!(adder.bus_value("z"), Ok(x + y), "{x} + {y}");
+    }
+}
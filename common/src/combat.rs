@@ -0,0 +1,733 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+pub mod search;
+pub mod shop;
+
+#[derive(Debug, Error)]
+pub enum CombatError {
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON Error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid stat line: {0}")]
+    Parse(String),
+    #[error("Unknown fighter '{0}'")]
+    UnknownFighter(String),
+    #[error("Unknown effect '{0}'")]
+    UnknownEffect(String),
+    #[error("Unknown spell '{0}'")]
+    UnknownSpell(String),
+    #[error("Unknown mode '{0}'")]
+    UnknownMode(String),
+    #[error("Unknown slot '{0}'")]
+    UnknownSlot(String),
+    #[error("Slot '{slot}' takes {min} to {max} items, got {count}")]
+    SlotCount {
+        slot: String,
+        count: usize,
+        min: usize,
+        max: usize,
+    },
+    #[error("Item '{0}' equipped twice")]
+    DuplicateItem(String),
+    #[error("Cannot cast '{0}'")]
+    CannotCast(String),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub hit_points: u64,
+    pub damage: u64,
+    pub armor: u64,
+    pub mana: u64,
+}
+
+impl Stats {
+    // Puzzle input style "Hit Points: 104" lines, missing stats are zero
+    pub fn parse(input: &str) -> Result<Stats, CombatError> {
+        let mut stats = Stats::default();
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            let invalid = || CombatError::Parse(line.to_string());
+            let (name, value) = line.split_once(':').ok_or_else(invalid)?;
+            let value = value.trim().parse::<u64>().map_err(|_| invalid())?;
+            match name.trim().to_ascii_lowercase().as_str() {
+                "hit points" => stats.hit_points = value,
+                "damage" => stats.damage = value,
+                "armor" => stats.armor = value,
+                "mana" => stats.mana = value,
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Player,
+    Boss,
+}
+
+impl Side {
+    pub fn opponent(&self) -> Side {
+        match self {
+            Side::Player => Side::Boss,
+            Side::Boss => Side::Player,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Slot {
+    pub name: String,
+    #[serde(default)]
+    pub min: usize,
+    pub max: usize,
+    #[serde(default)]
+    pub duplicates: bool, // whether one item may fill several places in the slot
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Item {
+    pub name: String,
+    pub slot: String,
+    pub cost: u64,
+    #[serde(default)]
+    pub damage: u64,
+    #[serde(default)]
+    pub armor: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectTarget {
+    Caster,
+    Opponent,
+}
+
+// A timed effect lives on one fighter and applies its per-turn values to that
+// fighter at the start of every turn, its armor counts for as long as it is
+// active
+#[derive(Debug, Clone, Deserialize)]
+pub struct Effect {
+    pub name: String,
+    pub duration: u32,
+    pub target: EffectTarget,
+    #[serde(default)]
+    pub armor: u64,
+    #[serde(default)]
+    pub damage: u64,
+    #[serde(default)]
+    pub heal: u64,
+    #[serde(default)]
+    pub mana: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpellDef {
+    pub name: String,
+    pub cost: u64,
+    #[serde(default)]
+    pub damage: u64,
+    #[serde(default)]
+    pub heal: u64,
+    pub effect: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spell {
+    pub name: String,
+    pub cost: u64,
+    pub damage: u64,
+    pub heal: u64,
+    pub effect: Option<usize>, // index into the ruleset's effects
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum RuleDef {
+    Bleed { side: Side, hit_points: u64 },
+    Poison { side: Side, damage: u64, every: u64 },
+}
+
+// Everything a fight can be configured with, usually loaded from a JSON file
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CombatData {
+    pub fighters: HashMap<String, Stats>,
+    pub slots: Vec<Slot>,
+    pub items: Vec<Item>,
+    pub effects: Vec<Effect>,
+    pub spells: Vec<SpellDef>,
+    pub modes: HashMap<String, Vec<RuleDef>>,
+}
+
+impl CombatData {
+    pub fn parse(json: &str) -> Result<CombatData, CombatError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<CombatData, CombatError> {
+        CombatData::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn fighter(&self, name: &str) -> Result<Stats, CombatError> {
+        self.fighters
+            .get(name)
+            .copied()
+            .ok_or_else(|| CombatError::UnknownFighter(name.to_string()))
+    }
+
+    pub fn items_in_slot<'a>(&'a self, slot: &'a str) -> impl Iterator<Item = &'a Item> + 'a {
+        self.items.iter().filter(move |item| item.slot == slot)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ActiveEffect {
+    pub effect: usize,
+    pub remaining: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Combatant {
+    pub hit_points: u64,
+    pub damage: u64,
+    pub armor: u64, // base armor, active effects add to it
+    pub mana: u64,
+    pub effects: Vec<ActiveEffect>,
+}
+
+impl Combatant {
+    pub fn new(stats: Stats) -> Self {
+        Combatant {
+            hit_points: stats.hit_points,
+            damage: stats.damage,
+            armor: stats.armor,
+            mana: stats.mana,
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.hit_points > 0
+    }
+
+    pub fn has_effect(&self, effect: usize) -> bool {
+        self.effects.iter().any(|active| active.effect == effect)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CombatState {
+    pub player: Combatant,
+    pub boss: Combatant,
+    pub turns: u64, // turns begun so far, the player's and the boss's
+}
+
+impl CombatState {
+    pub fn new(player: Combatant, boss: Combatant) -> Self {
+        CombatState { player, boss, turns: 0 }
+    }
+
+    // The round the current turn belongs to, counting from 1. Each fighter
+    // gets one turn per round, the player first.
+    pub fn round(&self) -> u64 {
+        self.turns.div_ceil(2)
+    }
+
+    pub fn fighter(&self, side: Side) -> &Combatant {
+        match side {
+            Side::Player => &self.player,
+            Side::Boss => &self.boss,
+        }
+    }
+
+    pub fn fighter_mut(&mut self, side: Side) -> &mut Combatant {
+        match side {
+            Side::Player => &mut self.player,
+            Side::Boss => &mut self.boss,
+        }
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.boss.is_alive() {
+            Some(Outcome::PlayerWins)
+        }
+        else if !self.player.is_alive() {
+            Some(Outcome::BossWins)
+        }
+        else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    PlayerWins,
+    BossWins,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Attack,
+    Cast(usize), // index into the ruleset's spells
+}
+
+// Watches the start of a turn as it happens, e.g. to narrate a fight. Each
+// method sees the state at that point of the turn.
+pub trait TurnObserver {
+    fn turn_started(&mut self, _side: Side, _state: &CombatState) {}
+    fn rules_applied(&mut self, _side: Side, _before: &CombatState, _after: &CombatState) {}
+    fn effects_ticking(&mut self, _side: Side, _state: &CombatState) {}
+}
+
+// Hook that runs at the start of every turn, before effects tick
+pub trait TurnRule: Debug {
+    fn start_of_turn(&self, side: Side, state: &mut CombatState);
+}
+
+// The fighter on `side` loses hit points at the start of each of its turns,
+// e.g. the player in hard mode
+#[derive(Debug, Clone, Copy)]
+pub struct Bleed {
+    pub side: Side,
+    pub hit_points: u64,
+}
+
+impl TurnRule for Bleed {
+    fn start_of_turn(&self, side: Side, state: &mut CombatState) {
+        if side == self.side {
+            let fighter = state.fighter_mut(side);
+            fighter.hit_points = fighter.hit_points.saturating_sub(self.hit_points);
+        }
+    }
+}
+
+// The fighter on `side` takes `damage` at the start of every `every`th of its
+// turns, a poison tick that comes and goes with the rounds rather than being
+// cast. An `every` of zero never ticks.
+#[derive(Debug, Clone, Copy)]
+pub struct Poison {
+    pub side: Side,
+    pub damage: u64,
+    pub every: u64,
+}
+
+impl TurnRule for Poison {
+    fn start_of_turn(&self, side: Side, state: &mut CombatState) {
+        if side == self.side && self.every > 0 && state.round().is_multiple_of(self.every) {
+            let fighter = state.fighter_mut(side);
+            fighter.hit_points = fighter.hit_points.saturating_sub(self.damage);
+        }
+    }
+}
+
+impl From<&RuleDef> for Box<dyn TurnRule> {
+    fn from(rule: &RuleDef) -> Self {
+        match *rule {
+            RuleDef::Bleed { side, hit_points } => Box::new(Bleed { side, hit_points }),
+            RuleDef::Poison { side, damage, every } => Box::new(Poison { side, damage, every }),
+        }
+    }
+}
+
+// Resolved spells, effects, slots and turn rules. A round is the player's
+// turn followed by the boss's, and every turn starts with the turn rules and
+// then the effect timers.
+#[derive(Debug)]
+pub struct Ruleset {
+    spells: Vec<Spell>,
+    effects: Vec<Effect>,
+    slots: Vec<Slot>,
+    rules: Vec<Box<dyn TurnRule>>,
+}
+
+impl Ruleset {
+    pub fn new(data: &CombatData) -> Result<Self, CombatError> {
+        let spells = data
+            .spells
+            .iter()
+            .map(|def| {
+                let effect = match &def.effect {
+                    Some(name) => Some(
+                        data.effects
+                            .iter()
+                            .position(|effect| &effect.name == name)
+                            .ok_or_else(|| CombatError::UnknownEffect(name.clone()))?,
+                    ),
+                    None => None,
+                };
+                Ok(Spell {
+                    name: def.name.clone(),
+                    cost: def.cost,
+                    damage: def.damage,
+                    heal: def.heal,
+                    effect,
+                })
+            })
+            .collect::<Result<Vec<Spell>, CombatError>>()?;
+
+        Ok(Ruleset {
+            spells,
+            effects: data.effects.clone(),
+            slots: data.slots.clone(),
+            rules: Vec::new(),
+        })
+    }
+
+    // The ruleset plus the turn rules listed under `mode` in the data
+    pub fn with_mode(data: &CombatData, mode: &str) -> Result<Self, CombatError> {
+        let mut ruleset = Ruleset::new(data)?;
+        let rules = data
+            .modes
+            .get(mode)
+            .ok_or_else(|| CombatError::UnknownMode(mode.to_string()))?;
+        ruleset.rules.extend(rules.iter().map(Box::<dyn TurnRule>::from));
+        Ok(ruleset)
+    }
+
+    pub fn add_rule<R: TurnRule + 'static>(&mut self, rule: R) {
+        self.rules.push(Box::new(rule));
+    }
+
+    pub fn spells(&self) -> &[Spell] {
+        &self.spells
+    }
+
+    pub fn effects(&self) -> &[Effect] {
+        &self.effects
+    }
+
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    pub fn spell_index(&self, name: &str) -> Result<usize, CombatError> {
+        self.spells
+            .iter()
+            .position(|spell| spell.name == name)
+            .ok_or_else(|| CombatError::UnknownSpell(name.to_string()))
+    }
+
+    // Base armor plus the armor of every active effect
+    pub fn armor(&self, fighter: &Combatant) -> u64 {
+        fighter.armor + fighter.effects.iter().map(|active| self.effects[active.effect].armor).sum::<u64>()
+    }
+
+    // A fighter wearing `items`, checked against the slot rules
+    pub fn equip(&self, stats: Stats, items: &[&Item]) -> Result<Combatant, CombatError> {
+        for (i, item) in items.iter().enumerate() {
+            let slot = self
+                .slots
+                .iter()
+                .find(|slot| slot.name == item.slot)
+                .ok_or_else(|| CombatError::UnknownSlot(item.slot.clone()))?;
+            if !slot.duplicates && items[..i].iter().any(|other| other.name == item.name) {
+                return Err(CombatError::DuplicateItem(item.name.clone()));
+            }
+        }
+
+        for slot in &self.slots {
+            let count = items.iter().filter(|item| item.slot == slot.name).count();
+            if count < slot.min || count > slot.max {
+                return Err(CombatError::SlotCount {
+                    slot: slot.name.clone(),
+                    count,
+                    min: slot.min,
+                    max: slot.max,
+                });
+            }
+        }
+
+        let mut fighter = Combatant::new(stats);
+        fighter.damage += items.iter().map(|item| item.damage).sum::<u64>();
+        fighter.armor += items.iter().map(|item| item.armor).sum::<u64>();
+        Ok(fighter)
+    }
+
+    fn tick_effects(&self, fighter: &mut Combatant) {
+        for active in fighter.effects.iter_mut() {
+            let effect = &self.effects[active.effect];
+            fighter.hit_points = fighter.hit_points.saturating_sub(effect.damage) + effect.heal;
+            fighter.mana += effect.mana;
+            active.remaining -= 1;
+        }
+        fighter.effects.retain(|active| active.remaining > 0);
+    }
+
+    // Turn rules and effect timers at the start of `side`'s turn
+    pub fn begin_turn(&self, state: &mut CombatState, side: Side) -> Option<Outcome> {
+        self.begin_turn_observed(state, side, None)
+    }
+
+    pub fn begin_turn_observed(
+        &self,
+        state: &mut CombatState,
+        side: Side,
+        mut observer: Option<&mut dyn TurnObserver>,
+    ) -> Option<Outcome> {
+        state.turns += 1;
+        if let Some(observer) = observer.as_mut() {
+            observer.turn_started(side, state);
+        }
+
+        let before = observer.is_some().then(|| state.clone());
+        for rule in &self.rules {
+            rule.start_of_turn(side, state);
+        }
+        if let (Some(observer), Some(before)) = (observer.as_mut(), &before) {
+            observer.rules_applied(side, before, state);
+        }
+        if let Some(outcome) = state.outcome() {
+            return Some(outcome);
+        }
+
+        if let Some(observer) = observer.as_mut() {
+            observer.effects_ticking(side, state);
+        }
+        self.tick_effects(&mut state.player);
+        self.tick_effects(&mut state.boss);
+        state.outcome()
+    }
+
+    pub fn can_cast(&self, state: &CombatState, caster: Side, spell: usize) -> bool {
+        let spell = &self.spells[spell];
+        if state.fighter(caster).mana < spell.cost {
+            return false;
+        }
+
+        spell.effect.is_none_or(|effect| {
+            let bearer = match self.effects[effect].target {
+                EffectTarget::Caster => caster,
+                EffectTarget::Opponent => caster.opponent(),
+            };
+            !state.fighter(bearer).has_effect(effect)
+        })
+    }
+
+    pub fn act(&self, state: &mut CombatState, side: Side, action: Action) -> Result<Option<Outcome>, CombatError> {
+        match action {
+            Action::Attack => {
+                let damage = state.fighter(side).damage;
+                let defender = state.fighter_mut(side.opponent());
+                let dealt = damage.saturating_sub(self.armor(defender)).max(1);
+                defender.hit_points = defender.hit_points.saturating_sub(dealt);
+            }
+            Action::Cast(index) => {
+                let spell = &self.spells[index];
+                if !self.can_cast(state, side, index) {
+                    return Err(CombatError::CannotCast(spell.name.clone()));
+                }
+
+                let caster = state.fighter_mut(side);
+                caster.mana -= spell.cost;
+                caster.hit_points += spell.heal;
+                let defender = state.fighter_mut(side.opponent());
+                defender.hit_points = defender.hit_points.saturating_sub(spell.damage);
+
+                if let Some(effect) = spell.effect {
+                    let bearer = match self.effects[effect].target {
+                        EffectTarget::Caster => side,
+                        EffectTarget::Opponent => side.opponent(),
+                    };
+                    let remaining = self.effects[effect].duration;
+                    // Kept sorted so equal states hash equally whatever the casting order
+                    let effects = &mut state.fighter_mut(bearer).effects;
+                    effects.push(ActiveEffect { effect, remaining });
+                    effects.sort_unstable();
+                }
+            }
+        }
+
+        Ok(state.outcome())
+    }
+
+    // The player's turn with `action`, then the boss's turn with an attack
+    pub fn play_round(&self, state: &mut CombatState, action: Action) -> Result<Option<Outcome>, CombatError> {
+        if let Some(outcome) = self.begin_turn(state, Side::Player) {
+            return Ok(Some(outcome));
+        }
+        if let Some(outcome) = self.act(state, Side::Player, action)? {
+            return Ok(Some(outcome));
+        }
+        if let Some(outcome) = self.begin_turn(state, Side::Boss) {
+            return Ok(Some(outcome));
+        }
+        self.act(state, Side::Boss, Action::Attack)
+    }
+
+    // Plays rounds until someone dies, asking `strategy` for the player's
+    // action at the start of each round
+    pub fn fight<F: FnMut(&CombatState) -> Action>(
+        &self,
+        mut state: CombatState,
+        mut strategy: F,
+    ) -> Result<Outcome, CombatError> {
+        loop {
+            let action = strategy(&state);
+            if let Some(outcome) = self.play_round(&mut state, action)? {
+                return Ok(outcome);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+const WIZARD_DATA: &str = r#"{
+    "fighters": { "wizard": { "hit_points": 10, "mana": 250 } },
+    "effects": [
+        { "name": "Shield", "duration": 6, "target": "caster", "armor": 7 },
+        { "name": "Poison", "duration": 6, "target": "opponent", "damage": 3 },
+        { "name": "Recharge", "duration": 5, "target": "caster", "mana": 101 }
+    ],
+    "spells": [
+        { "name": "Magic Missile", "cost": 53, "damage": 4 },
+        { "name": "Drain", "cost": 73, "damage": 2, "heal": 2 },
+        { "name": "Shield", "cost": 113, "effect": "Shield" },
+        { "name": "Poison", "cost": 173, "effect": "Poison" },
+        { "name": "Recharge", "cost": 229, "effect": "Recharge" }
+    ],
+    "modes": {
+        "hard": [{ "rule": "bleed", "side": "player", "hit_points": 1 }],
+        "swamp": [
+            { "rule": "poison", "side": "player", "damage": 2, "every": 2 },
+            { "rule": "poison", "side": "boss", "damage": 5, "every": 3 }
+        ]
+    }
+}"#;
+
+#[test]
+fn test_combat_puzzle_example_fights() {
+    let data = CombatData::parse(WIZARD_DATA).unwrap();
+    let ruleset = Ruleset::new(&data).unwrap();
+    let wizard = Combatant::new(data.fighter("wizard").unwrap());
+
+    let boss = Combatant::new(Stats::parse("Hit Points: 13\nDamage: 8").unwrap());
+    let state = CombatState::new(wizard.clone(), boss);
+    let mut plan = ["Poison", "Magic Missile"].into_iter();
+    let outcome = ruleset.fight(state, |_| Action::Cast(ruleset.spell_index(plan.next().unwrap()).unwrap()));
+    assert_eq!(outcome.unwrap(), Outcome::PlayerWins);
+
+    let boss = Combatant::new(Stats::parse("Hit Points: 14\nDamage: 8").unwrap());
+    let mut state = CombatState::new(wizard, boss);
+    for spell in ["Recharge", "Shield", "Drain", "Poison"] {
+        let action = Action::Cast(ruleset.spell_index(spell).unwrap());
+        assert_eq!(ruleset.play_round(&mut state, action).unwrap(), None);
+    }
+    // Shield has one turn left and wears off at the start of the next round
+    assert_eq!(ruleset.armor(&state.player), 7);
+    let missile = Action::Cast(ruleset.spell_index("Magic Missile").unwrap());
+    assert_eq!(ruleset.play_round(&mut state, missile).unwrap(), Some(Outcome::PlayerWins));
+    assert_eq!(state.player.hit_points, 1);
+    assert_eq!(state.player.mana, 114);
+}
+
+#[test]
+fn test_combat_rules_and_casting() {
+    let data = CombatData::parse(WIZARD_DATA).unwrap();
+    let boss = Combatant::new(Stats { hit_points: 50, damage: 1, ..Stats::default() });
+    let wizard = Combatant::new(data.fighter("wizard").unwrap());
+
+    // Nine hit points against one point of bleeding and one of damage per
+    // round, the bleed finishes the player at the start of the fifth round
+    let hard = Ruleset::with_mode(&data, "hard").unwrap();
+    let mut state = CombatState::new(wizard.clone(), boss.clone());
+    state.player.hit_points = 9;
+    let missile = hard.spell_index("Magic Missile").unwrap();
+    let mut rounds = 0;
+    let outcome = hard.fight(state, |_| {
+        rounds += 1;
+        Action::Cast(missile)
+    });
+    assert_eq!(outcome.unwrap(), Outcome::BossWins);
+    assert_eq!(rounds, 5);
+
+    let normal = Ruleset::new(&data).unwrap();
+    let mut state = CombatState::new(wizard, boss);
+    let poison = normal.spell_index("Poison").unwrap();
+    assert_eq!(normal.play_round(&mut state, Action::Cast(poison)).unwrap(), None);
+    assert!(!normal.can_cast(&state, Side::Player, poison));
+    assert!(matches!(
+        normal.play_round(&mut state, Action::Cast(poison)),
+        Err(CombatError::CannotCast(spell)) if spell == "Poison"
+    ));
+    assert!(matches!(Ruleset::with_mode(&data, "nightmare"), Err(CombatError::UnknownMode(_))));
+    assert!(matches!(data.fighter("knight"), Err(CombatError::UnknownFighter(_))));
+    assert!(matches!(CombatData::parse("{\"fighters\": 3}"), Err(CombatError::Json(_))));
+    assert!(matches!(CombatData::load("missing/combat.json"), Err(CombatError::Io(_))));
+}
+
+#[test]
+fn test_combat_poison_rule_from_data() {
+    let data = CombatData::parse(WIZARD_DATA).unwrap();
+    let swamp = Ruleset::with_mode(&data, "swamp").unwrap();
+    let wizard = Combatant::new(Stats { hit_points: 20, ..Stats::default() });
+    let boss = Combatant::new(Stats { hit_points: 30, damage: 1, ..Stats::default() });
+    let mut state = CombatState::new(wizard, boss);
+
+    // The player's poison ticks in even rounds and the boss's every third
+    let mut hit_points = Vec::new();
+    for _ in 0..6 {
+        assert_eq!(swamp.play_round(&mut state, Action::Attack).unwrap(), None);
+        hit_points.push((state.player.hit_points, state.boss.hit_points));
+    }
+    assert_eq!(hit_points, [(19, 29), (16, 28), (15, 22), (12, 21), (11, 20), (8, 14)]);
+    assert_eq!((state.turns, state.round()), (12, 6));
+
+    let never = Poison { side: Side::Boss, damage: 5, every: 0 };
+    never.start_of_turn(Side::Boss, &mut state);
+    assert_eq!(state.boss.hit_points, 14);
+}
+
+#[test]
+fn test_combat_equipment_slots() {
+    let data = CombatData::parse(
+        r#"{
+            "slots": [
+                { "name": "weapon", "min": 1, "max": 1 },
+                { "name": "ring", "max": 2 }
+            ],
+            "items": [
+                { "name": "Dagger", "slot": "weapon", "cost": 8, "damage": 4 },
+                { "name": "Damage +1", "slot": "ring", "cost": 25, "damage": 1 },
+                { "name": "Defense +2", "slot": "ring", "cost": 40, "armor": 2 },
+                { "name": "Helmet", "slot": "head", "cost": 10, "armor": 1 }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let ruleset = Ruleset::new(&data).unwrap();
+    let item = |name: &str| data.items.iter().find(|item| item.name == name).unwrap();
+    let stats = Stats { hit_points: 8, ..Stats::default() };
+
+    let player = ruleset.equip(stats, &[item("Dagger"), item("Damage +1"), item("Defense +2")]).unwrap();
+    assert_eq!((player.damage, player.armor), (5, 2));
+
+    // The puzzle's example: 8 hit points, 5 damage, 5 armor against 12/7/2
+    let player = Combatant { armor: 5, ..player };
+    let boss = Combatant::new(Stats::parse("Hit Points: 12\nDamage: 7\nArmor: 2").unwrap());
+    let state = CombatState::new(player, boss);
+    assert_eq!(ruleset.fight(state, |_| Action::Attack).unwrap(), Outcome::PlayerWins);
+
+    assert!(matches!(ruleset.equip(stats, &[]), Err(CombatError::SlotCount { count: 0, .. })));
+    assert!(matches!(
+        ruleset.equip(stats, &[item("Dagger"), item("Damage +1"), item("Damage +1")]),
+        Err(CombatError::DuplicateItem(name)) if name == "Damage +1"
+    ));
+    assert!(matches!(
+        ruleset.equip(stats, &[item("Dagger"), item("Helmet")]),
+        Err(CombatError::UnknownSlot(slot)) if slot == "head"
+    ));
+    assert_eq!(data.items_in_slot("ring").count(), 2);
+    assert!(Stats::parse("Speed: 3").is_err());
+}
@@ -0,0 +1,406 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::{Action, CombatError, CombatState, Combatant, Outcome, Ruleset, Side, TurnObserver};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpellPlan {
+    pub mana_spent: u64,
+    pub spells: Vec<usize>, // indices into the ruleset's spells, one per round
+}
+
+struct Node {
+    state: CombatState, // at the start of a player turn, before it begins
+    parent: Option<(usize, usize)>, // (node, spell cast to get here)
+    won: bool,
+}
+
+// Dijkstra over fight states keyed by mana spent. Nodes are the states at the
+// start of the player's turns plus one terminal node per won fight, so the
+// first won node popped is a cheapest win.
+pub fn cheapest_win(ruleset: &Ruleset, start: &CombatState) -> Option<SpellPlan> {
+    let mut nodes = vec![Node {
+        state: start.clone(),
+        parent: None,
+        won: false,
+    }];
+    let mut best = HashMap::from([(start.clone(), 0)]);
+    let mut queue = BinaryHeap::from([Reverse((0, 0))]);
+
+    while let Some(Reverse((mana_spent, id))) = queue.pop() {
+        if nodes[id].won {
+            let mut spells = Vec::new();
+            let mut current = id;
+            while let Some((parent, spell)) = nodes[current].parent {
+                spells.push(spell);
+                current = parent;
+            }
+            spells.reverse();
+            return Some(SpellPlan { mana_spent, spells });
+        }
+        if best.get(&nodes[id].state).is_some_and(|&cost| cost < mana_spent) {
+            continue;
+        }
+
+        let mut state = nodes[id].state.clone();
+        match ruleset.begin_turn(&mut state, Side::Player) {
+            Some(Outcome::PlayerWins) => {
+                // Nothing cast this round, the win is a free step back to this node
+                nodes.push(Node {
+                    state,
+                    parent: nodes[id].parent,
+                    won: true,
+                });
+                queue.push(Reverse((mana_spent, nodes.len() - 1)));
+                continue;
+            }
+            Some(Outcome::BossWins) => continue,
+            None => (),
+        }
+
+        for (spell, definition) in ruleset.spells().iter().enumerate() {
+            if !ruleset.can_cast(&state, Side::Player, spell) {
+                continue;
+            }
+
+            let mut next = state.clone();
+            let outcome = match ruleset.act(&mut next, Side::Player, Action::Cast(spell)) {
+                Ok(None) => match ruleset.begin_turn(&mut next, Side::Boss) {
+                    None => ruleset.act(&mut next, Side::Boss, Action::Attack).ok().flatten(),
+                    outcome => outcome,
+                },
+                Ok(outcome) => outcome,
+                Err(_) => continue,
+            };
+
+            let cost = mana_spent + definition.cost;
+            let won = match outcome {
+                Some(Outcome::BossWins) => continue,
+                Some(Outcome::PlayerWins) => true,
+                None => {
+                    if best.get(&next).is_some_and(|&known| known <= cost) {
+                        continue;
+                    }
+                    best.insert(next.clone(), cost);
+                    false
+                }
+            };
+
+            nodes.push(Node {
+                state: next,
+                parent: Some((id, spell)),
+                won,
+            });
+            queue.push(Reverse((cost, nodes.len() - 1)));
+        }
+    }
+
+    None
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Player => "Player",
+        Side::Boss => "Boss",
+    }
+}
+
+fn plural(count: u64, word: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, word)
+    }
+    else {
+        format!("{} {}s", count, word)
+    }
+}
+
+fn describe_fighters(ruleset: &Ruleset, state: &CombatState, log: &mut Vec<String>) {
+    let player = &state.player;
+    log.push(format!(
+        "- Player has {}, {} armor, {} mana",
+        plural(player.hit_points, "hit point"),
+        ruleset.armor(player),
+        player.mana
+    ));
+    log.push(format!("- Boss has {}", plural(state.boss.hit_points, "hit point")));
+}
+
+fn describe_effects(ruleset: &Ruleset, fighter: &Combatant, log: &mut Vec<String>) {
+    for active in &fighter.effects {
+        let effect = &ruleset.effects()[active.effect];
+        let remaining = active.remaining - 1;
+        let mut parts = Vec::new();
+        if effect.damage > 0 {
+            parts.push(format!("deals {} damage", effect.damage));
+        }
+        if effect.heal > 0 {
+            parts.push(format!("heals {}", plural(effect.heal, "hit point")));
+        }
+        if effect.mana > 0 {
+            parts.push(format!("provides {} mana", effect.mana));
+        }
+
+        if parts.is_empty() {
+            log.push(format!("{}'s timer is now {}.", effect.name, remaining));
+        }
+        else {
+            log.push(format!("{} {}; its timer is now {}.", effect.name, parts.join(" and "), remaining));
+        }
+        if remaining == 0 && effect.armor > 0 {
+            log.push(format!("{} wears off, decreasing armor by {}.", effect.name, effect.armor));
+        }
+        else if remaining == 0 {
+            log.push(format!("{} wears off.", effect.name));
+        }
+    }
+}
+
+// Narrates the start of a turn into the replay log
+struct Narrator<'a> {
+    ruleset: &'a Ruleset,
+    log: &'a mut Vec<String>,
+}
+
+impl TurnObserver for Narrator<'_> {
+    fn turn_started(&mut self, side: Side, state: &CombatState) {
+        self.log.push(format!("-- {} turn --", side_name(side)));
+        describe_fighters(self.ruleset, state, self.log);
+    }
+
+    fn rules_applied(&mut self, _side: Side, before: &CombatState, after: &CombatState) {
+        for fighter in [Side::Player, Side::Boss] {
+            let (before, after) = (before.fighter(fighter).hit_points, after.fighter(fighter).hit_points);
+            if after < before {
+                self.log.push(format!("{} loses {}.", side_name(fighter), plural(before - after, "hit point")));
+            }
+            else if after > before {
+                self.log.push(format!("{} gains {}.", side_name(fighter), plural(after - before, "hit point")));
+            }
+        }
+    }
+
+    fn effects_ticking(&mut self, _side: Side, state: &CombatState) {
+        describe_effects(self.ruleset, &state.player, self.log);
+        describe_effects(self.ruleset, &state.boss, self.log);
+    }
+}
+
+fn begin_turn_logged(ruleset: &Ruleset, state: &mut CombatState, side: Side, log: &mut Vec<String>) -> Option<Outcome> {
+    ruleset.begin_turn_observed(state, side, Some(&mut Narrator { ruleset, log }))
+}
+
+fn act_logged(
+    ruleset: &Ruleset,
+    state: &mut CombatState,
+    side: Side,
+    action: Action,
+    log: &mut Vec<String>,
+) -> Result<Option<Outcome>, CombatError> {
+    let armor = ruleset.armor(state.fighter(side.opponent()));
+    let before = state.fighter(side.opponent()).hit_points;
+    let outcome = ruleset.act(state, side, action)?;
+    let dealt = before - state.fighter(side.opponent()).hit_points;
+
+    match action {
+        Action::Attack if armor > 0 => log.push(format!(
+            "{} attacks for {} - {} = {} damage.",
+            side_name(side),
+            state.fighter(side).damage,
+            armor,
+            dealt
+        )),
+        Action::Attack => log.push(format!("{} attacks for {} damage.", side_name(side), dealt)),
+        Action::Cast(index) => {
+            let spell = &ruleset.spells()[index];
+            let mut line = format!("{} casts {}", side_name(side), spell.name);
+            if spell.damage > 0 {
+                line += &format!(", dealing {} damage", spell.damage);
+            }
+            if spell.heal > 0 {
+                line += &format!(", healing {}", plural(spell.heal, "hit point"));
+            }
+            log.push(line + ".");
+        }
+    }
+
+    Ok(outcome)
+}
+
+// Plays the fight with the player casting `spells` in order and narrates it
+// turn by turn. Stops early when someone dies; a fight still undecided after
+// the last spell ends at the start of the next player turn.
+pub fn replay(ruleset: &Ruleset, start: &CombatState, spells: &[usize]) -> Result<Vec<String>, CombatError> {
+    let mut state = start.clone();
+    let mut log = Vec::new();
+    let mut outcome = None;
+    for &spell in spells {
+        outcome = begin_turn_logged(ruleset, &mut state, Side::Player, &mut log);
+        if outcome.is_none() {
+            outcome = act_logged(ruleset, &mut state, Side::Player, Action::Cast(spell), &mut log)?;
+        }
+        if outcome.is_none() {
+            log.push(String::new());
+            outcome = begin_turn_logged(ruleset, &mut state, Side::Boss, &mut log);
+        }
+        if outcome.is_none() {
+            outcome = act_logged(ruleset, &mut state, Side::Boss, Action::Attack, &mut log)?;
+        }
+        if outcome.is_some() {
+            break;
+        }
+        log.push(String::new());
+    }
+
+    if outcome.is_none() {
+        outcome = begin_turn_logged(ruleset, &mut state, Side::Player, &mut log);
+    }
+    match outcome {
+        Some(Outcome::PlayerWins) => log.push("This kills the boss, and the player wins.".to_string()),
+        Some(Outcome::BossWins) => log.push("This kills the player, and the boss wins.".to_string()),
+        None => (),
+    }
+
+    Ok(log)
+}
+
+#[cfg(test)]
+fn exhaustive_cheapest_win(ruleset: &Ruleset, state: &CombatState, mana_spent: u64, best: &mut Option<u64>) {
+    // Poison can finish the boss before anything is cast
+    let mut probe = state.clone();
+    if ruleset.begin_turn(&mut probe, Side::Player) == Some(Outcome::PlayerWins) {
+        *best = Some(best.map_or(mana_spent, |best| best.min(mana_spent)));
+        return;
+    }
+
+    for (spell, definition) in ruleset.spells().iter().enumerate() {
+        let cost = mana_spent + definition.cost;
+        if best.is_some_and(|best| cost >= best) {
+            continue;
+        }
+
+        let mut next = state.clone();
+        match ruleset.play_round(&mut next, Action::Cast(spell)) {
+            Ok(Some(Outcome::PlayerWins)) => *best = Some(cost),
+            Ok(None) => exhaustive_cheapest_win(ruleset, &next, cost, best),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+fn wizard_fight(boss_hit_points: u64, boss_damage: u64) -> CombatState {
+    use super::Stats;
+
+    let player = Stats { hit_points: 10, mana: 250, ..Stats::default() };
+    let boss = Stats { hit_points: boss_hit_points, damage: boss_damage, ..Stats::default() };
+    CombatState::new(Combatant::new(player), Combatant::new(boss))
+}
+
+#[test]
+fn test_cheapest_win_matches_exhaustive_search() {
+    let data = super::CombatData::parse(super::WIZARD_DATA).unwrap();
+    for mode in [None, Some("hard")] {
+        let ruleset = match mode {
+            Some(mode) => Ruleset::with_mode(&data, mode).unwrap(),
+            None => Ruleset::new(&data).unwrap(),
+        };
+        for boss_hit_points in [5, 13, 14, 20, 26] {
+            for boss_damage in [2, 5, 8] {
+                let start = wizard_fight(boss_hit_points, boss_damage);
+                let mut expected = None;
+                exhaustive_cheapest_win(&ruleset, &start, 0, &mut expected);
+
+                let plan = cheapest_win(&ruleset, &start);
+                assert_eq!(plan.as_ref().map(|plan| plan.mana_spent), expected);
+                if let Some(plan) = plan {
+                    let cost = plan.spells.iter().map(|&spell| ruleset.spells()[spell].cost).sum::<u64>();
+                    assert_eq!(cost, plan.mana_spent);
+                    let log = replay(&ruleset, &start, &plan.spells).unwrap();
+                    assert_eq!(log.last().unwrap(), "This kills the boss, and the player wins.");
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_replay_matches_puzzle_narrative() {
+    let data = super::CombatData::parse(super::WIZARD_DATA).unwrap();
+    let ruleset = Ruleset::new(&data).unwrap();
+    let start = wizard_fight(13, 8);
+    let plan = cheapest_win(&ruleset, &start).unwrap();
+    let names = plan.spells.iter().map(|&spell| ruleset.spells()[spell].name.as_str()).collect::<Vec<&str>>();
+    assert_eq!((plan.mana_spent, names), (226, vec!["Poison", "Magic Missile"]));
+
+    let expected = "\
+-- Player turn --
+- Player has 10 hit points, 0 armor, 250 mana
+- Boss has 13 hit points
+Player casts Poison.
+
+-- Boss turn --
+- Player has 10 hit points, 0 armor, 77 mana
+- Boss has 13 hit points
+Poison deals 3 damage; its timer is now 5.
+Boss attacks for 8 damage.
+
+-- Player turn --
+- Player has 2 hit points, 0 armor, 77 mana
+- Boss has 10 hit points
+Poison deals 3 damage; its timer is now 4.
+Player casts Magic Missile, dealing 4 damage.
+
+-- Boss turn --
+- Player has 2 hit points, 0 armor, 24 mana
+- Boss has 3 hit points
+Poison deals 3 damage; its timer is now 3.
+This kills the boss, and the player wins.";
+    assert_eq!(replay(&ruleset, &start, &plan.spells).unwrap().join("\n"), expected);
+
+    let shield = ruleset.spell_index("Shield").unwrap();
+    let missile = ruleset.spell_index("Magic Missile").unwrap();
+    let log = replay(&ruleset, &wizard_fight(50, 8), &[shield, missile]).unwrap();
+    assert!(log.contains(&"Boss attacks for 8 - 7 = 1 damage.".to_string()));
+    assert!(matches!(
+        replay(&ruleset, &start, &[shield, shield]),
+        Err(CombatError::CannotCast(spell)) if spell == "Shield"
+    ));
+}
+
+#[test]
+fn test_replay_follows_custom_turn_rules() {
+    use super::{Poison, TurnRule};
+
+    // Regains hit points at the start of each of its turns
+    #[derive(Debug)]
+    struct Regenerate(Side, u64);
+
+    impl TurnRule for Regenerate {
+        fn start_of_turn(&self, side: Side, state: &mut CombatState) {
+            if side == self.0 {
+                state.fighter_mut(side).hit_points += self.1;
+            }
+        }
+    }
+
+    let data = super::CombatData::parse(super::WIZARD_DATA).unwrap();
+    let mut ruleset = Ruleset::new(&data).unwrap();
+    ruleset.add_rule(Regenerate(Side::Boss, 2));
+    ruleset.add_rule(Poison { side: Side::Player, damage: 1, every: 2 });
+    let start = wizard_fight(20, 1);
+    let missile = ruleset.spell_index("Magic Missile").unwrap();
+    let log = replay(&ruleset, &start, &[missile; 4]).unwrap();
+    assert_eq!(log.iter().filter(|line| *line == "Boss gains 2 hit points.").count(), 4);
+    assert_eq!(log.iter().filter(|line| *line == "Player loses 1 hit point.").count(), 2);
+
+    // The narrated fight ends where the real one does
+    let mut state = start.clone();
+    for _ in 0..4 {
+        assert_eq!(ruleset.play_round(&mut state, Action::Cast(missile)).unwrap(), None);
+    }
+    let mut expected = Vec::new();
+    describe_fighters(&ruleset, &state, &mut expected);
+    let start_of_last_turn = log.iter().rposition(|line| line == "-- Player turn --").unwrap();
+    assert_eq!(log[start_of_last_turn + 1..start_of_last_turn + 3], expected);
+    assert_eq!(expected[1], "- Boss has 12 hit points");
+}
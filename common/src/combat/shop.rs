@@ -0,0 +1,209 @@
+use super::{CombatData, CombatError, Item, Outcome, Slot, Stats};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Loadout<'a> {
+    pub items: Vec<&'a Item>,
+    pub cost: u64,
+    pub damage: u64,
+    pub armor: u64,
+}
+
+impl<'a> Loadout<'a> {
+    pub fn new(items: Vec<&'a Item>) -> Self {
+        Loadout {
+            cost: items.iter().map(|item| item.cost).sum(),
+            damage: items.iter().map(|item| item.damage).sum(),
+            armor: items.iter().map(|item| item.armor).sum(),
+            items,
+        }
+    }
+
+    // `stats` with the items' damage and armor added
+    pub fn equip(&self, stats: Stats) -> Stats {
+        Stats {
+            damage: stats.damage + self.damage,
+            armor: stats.armor + self.armor,
+            ..stats
+        }
+    }
+}
+
+fn hits_to_kill(hit_points: u64, damage: u64, armor: u64) -> u64 {
+    hit_points.div_ceil(damage.saturating_sub(armor).max(1))
+}
+
+// Outcome of a plain attack-for-attack fight with the player striking first,
+// without playing it out: each side needs ceil(hit points / damage per hit)
+// hits and the player wins ties
+pub fn fight_outcome(player: Stats, boss: Stats) -> Outcome {
+    let player_hits = hits_to_kill(boss.hit_points, player.damage, boss.armor);
+    let boss_hits = hits_to_kill(player.hit_points, boss.damage, player.armor);
+    if player_hits <= boss_hits {
+        Outcome::PlayerWins
+    }
+    else {
+        Outcome::BossWins
+    }
+}
+
+// Every way to fill one slot, as multisets of its items when duplicates are
+// allowed and as plain subsets otherwise
+fn slot_choices<'a>(slot: &Slot, items: &[&'a Item]) -> Vec<Vec<&'a Item>> {
+    fn extend<'a>(
+        slot: &Slot,
+        items: &[&'a Item],
+        start: usize,
+        current: &mut Vec<&'a Item>,
+        choices: &mut Vec<Vec<&'a Item>>,
+    ) {
+        if current.len() >= slot.min {
+            choices.push(current.clone());
+        }
+        if current.len() == slot.max {
+            return;
+        }
+
+        for i in start..items.len() {
+            current.push(items[i]);
+            extend(slot, items, if slot.duplicates { i } else { i + 1 }, current, choices);
+            current.pop();
+        }
+    }
+
+    let mut choices = Vec::new();
+    extend(slot, items, 0, &mut Vec::new(), &mut choices);
+    choices
+}
+
+// The item catalog and slot rules of a combat data file
+#[derive(Debug, Clone, Copy)]
+pub struct Shop<'a> {
+    slots: &'a [Slot],
+    items: &'a [Item],
+}
+
+impl<'a> Shop<'a> {
+    pub fn new(data: &'a CombatData) -> Result<Self, CombatError> {
+        if let Some(item) = data.items.iter().find(|item| !data.slots.iter().any(|slot| slot.name == item.slot)) {
+            return Err(CombatError::UnknownSlot(item.slot.clone()));
+        }
+
+        Ok(Shop {
+            slots: &data.slots,
+            items: &data.items,
+        })
+    }
+
+    // Every combination of items that satisfies the slot rules
+    pub fn loadouts(&self) -> Vec<Loadout<'a>> {
+        let mut combinations = vec![Vec::new()];
+        for slot in self.slots {
+            let items = self.items.iter().filter(|item| item.slot == slot.name).collect::<Vec<&Item>>();
+            let choices = slot_choices(slot, &items);
+            combinations = combinations
+                .iter()
+                .flat_map(|combination| {
+                    choices.iter().map(move |choice| {
+                        let mut extended = combination.clone();
+                        extended.extend(choice);
+                        extended
+                    })
+                })
+                .collect();
+        }
+
+        combinations.into_iter().map(Loadout::new).collect()
+    }
+
+    pub fn cheapest_win(&self, player: Stats, boss: Stats) -> Option<Loadout<'a>> {
+        self.loadouts()
+            .into_iter()
+            .filter(|loadout| fight_outcome(loadout.equip(player), boss) == Outcome::PlayerWins)
+            .min_by_key(|loadout| loadout.cost)
+    }
+
+    pub fn priciest_loss(&self, player: Stats, boss: Stats) -> Option<Loadout<'a>> {
+        self.loadouts()
+            .into_iter()
+            .filter(|loadout| fight_outcome(loadout.equip(player), boss) == Outcome::BossWins)
+            .max_by_key(|loadout| loadout.cost)
+    }
+}
+
+#[cfg(test)]
+const SHOP_DATA: &str = r#"{
+    "slots": [
+        { "name": "weapon", "min": 1, "max": 1 },
+        { "name": "armor", "max": 1 },
+        { "name": "ring", "max": 2 }
+    ],
+    "items": [
+        { "name": "Dagger", "slot": "weapon", "cost": 8, "damage": 4 },
+        { "name": "Shortsword", "slot": "weapon", "cost": 10, "damage": 5 },
+        { "name": "Warhammer", "slot": "weapon", "cost": 25, "damage": 6 },
+        { "name": "Longsword", "slot": "weapon", "cost": 40, "damage": 7 },
+        { "name": "Greataxe", "slot": "weapon", "cost": 74, "damage": 8 },
+        { "name": "Leather", "slot": "armor", "cost": 13, "armor": 1 },
+        { "name": "Chainmail", "slot": "armor", "cost": 31, "armor": 2 },
+        { "name": "Splintmail", "slot": "armor", "cost": 53, "armor": 3 },
+        { "name": "Bandedmail", "slot": "armor", "cost": 75, "armor": 4 },
+        { "name": "Platemail", "slot": "armor", "cost": 102, "armor": 5 },
+        { "name": "Damage +1", "slot": "ring", "cost": 25, "damage": 1 },
+        { "name": "Damage +2", "slot": "ring", "cost": 50, "damage": 2 },
+        { "name": "Damage +3", "slot": "ring", "cost": 100, "damage": 3 },
+        { "name": "Defense +1", "slot": "ring", "cost": 20, "armor": 1 },
+        { "name": "Defense +2", "slot": "ring", "cost": 40, "armor": 2 },
+        { "name": "Defense +3", "slot": "ring", "cost": 80, "armor": 3 }
+    ]
+}"#;
+
+#[test]
+fn test_shop_loadouts_follow_slot_rules() {
+    let mut data = CombatData::parse(SHOP_DATA).unwrap();
+    let shop = Shop::new(&data).unwrap();
+    let loadouts = shop.loadouts();
+    // 5 weapons, 6 armor choices and 1 + 6 + 15 ring choices
+    assert_eq!(loadouts.len(), 5 * 6 * 22);
+
+    let ruleset = super::Ruleset::new(&data).unwrap();
+    for loadout in &loadouts {
+        assert!(ruleset.equip(Stats::default(), &loadout.items).is_ok());
+    }
+
+    data.slots[2].duplicates = true;
+    assert_eq!(Shop::new(&data).unwrap().loadouts().len(), 5 * 6 * 28);
+
+    data.items.push(Item {
+        name: "Cape".to_string(),
+        slot: "back".to_string(),
+        cost: 1,
+        damage: 0,
+        armor: 0,
+    });
+    assert!(matches!(Shop::new(&data), Err(CombatError::UnknownSlot(slot)) if slot == "back"));
+}
+
+#[test]
+fn test_fight_outcome_matches_simulation() {
+    use super::{Action, CombatState, Combatant, Ruleset};
+
+    let data = CombatData::parse(SHOP_DATA).unwrap();
+    let ruleset = Ruleset::new(&data).unwrap();
+    let shop = Shop::new(&data).unwrap();
+    let player = Stats { hit_points: 100, ..Stats::default() };
+    for boss in [(104, 8, 1), (100, 8, 2), (109, 8, 2), (1, 100, 100), (300, 1, 0)] {
+        let boss = Stats { hit_points: boss.0, damage: boss.1, armor: boss.2, mana: 0 };
+        for loadout in shop.loadouts() {
+            let state = CombatState::new(Combatant::new(loadout.equip(player)), Combatant::new(boss));
+            let simulated = ruleset.fight(state, |_| Action::Attack).unwrap();
+            assert_eq!(fight_outcome(loadout.equip(player), boss), simulated);
+        }
+    }
+
+    let boss = Stats { hit_points: 104, damage: 8, armor: 1, mana: 0 };
+    assert_eq!(shop.cheapest_win(player, boss).unwrap().cost, 78);
+    assert_eq!(shop.priciest_loss(player, boss).unwrap().cost, 148);
+
+    let unbeatable = Stats { hit_points: 1000, damage: 1000, ..Stats::default() };
+    assert_eq!(shop.cheapest_win(player, unbeatable), None);
+}
@@ -0,0 +1,296 @@
+pub mod polyomino;
+
+// Knuth's Algorithm X over a dancing links matrix.
+//
+// Primary columns must be covered exactly once and are the only columns the
+// search branches on. Secondary columns may be covered at most `capacity`
+// times (once by default); a secondary column is only unlinked when its
+// capacity is used up, so interchangeable rows (e.g. identical pieces) can
+// share a column without the search visiting every permutation of them.
+#[derive(Debug, Clone)]
+pub struct Dlx {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    row: Vec<usize>,
+    size: Vec<usize>,
+    remaining: Vec<usize>,
+    num_primary: usize,
+    num_columns: usize,
+    num_rows: usize,
+}
+
+impl Dlx {
+    const ROOT: usize = 0;
+
+    pub fn new(num_primary: usize, num_secondary: usize) -> Self {
+        Dlx::with_capacities(num_primary, &vec![1; num_secondary])
+    }
+
+    pub fn with_capacities(num_primary: usize, secondary_capacities: &[usize]) -> Self {
+        let num_columns = num_primary + secondary_capacities.len();
+        let mut dlx = Dlx {
+            left: Vec::with_capacity(num_columns + 1),
+            right: Vec::with_capacity(num_columns + 1),
+            up: Vec::with_capacity(num_columns + 1),
+            down: Vec::with_capacity(num_columns + 1),
+            column: Vec::with_capacity(num_columns + 1),
+            row: Vec::with_capacity(num_columns + 1),
+            size: vec![0; num_columns + 1],
+            remaining: vec![1; num_columns + 1],
+            num_primary,
+            num_columns,
+            num_rows: 0,
+        };
+
+        // Node 0 is the root, nodes 1..=num_columns are the column headers
+        for node in 0..=num_columns {
+            dlx.up.push(node);
+            dlx.down.push(node);
+            dlx.column.push(node);
+            dlx.row.push(usize::MAX);
+            if node <= num_primary {
+                dlx.left.push(if node == 0 { num_primary } else { node - 1 });
+                dlx.right.push(if node == num_primary { 0 } else { node + 1 });
+            }
+            else {
+                // Secondary columns are never linked into the root list
+                dlx.left.push(node);
+                dlx.right.push(node);
+            }
+        }
+
+        for (i, &capacity) in secondary_capacities.iter().enumerate() {
+            dlx.remaining[num_primary + i + 1] = capacity;
+        }
+
+        dlx
+    }
+
+    pub fn num_primary(&self) -> usize {
+        self.num_primary
+    }
+
+    pub fn num_secondary(&self) -> usize {
+        self.num_columns - self.num_primary
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    // Columns are numbered with the primary columns first, then the secondary
+    // ones. Returns the index of the new row, which is what solutions contain.
+    pub fn add_row(&mut self, columns: &[usize]) -> usize {
+        assert!(!columns.is_empty(), "DLX rows must cover at least one column");
+        let first = self.left.len();
+        for (i, &col) in columns.iter().enumerate() {
+            assert!(col < self.num_columns, "Column {} out of range", col);
+            let header = col + 1;
+            let node = first + i;
+            let last = self.up[header];
+            self.column.push(header);
+            self.row.push(self.num_rows);
+            self.up.push(last);
+            self.down.push(header);
+            self.down[last] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+            self.left.push(if i == 0 { first + columns.len() - 1 } else { node - 1 });
+            self.right.push(if i == columns.len() - 1 { first } else { node + 1 });
+        }
+
+        self.num_rows += 1;
+        self.num_rows - 1
+    }
+
+    pub fn solve_first(&mut self) -> Option<Vec<usize>> {
+        let mut first = None;
+        self.search(|solution| {
+            first = Some(solution.to_vec());
+            false
+        });
+
+        first
+    }
+
+    pub fn solve_all(&mut self, limit: Option<usize>) -> Vec<Vec<usize>> {
+        let mut solutions = Vec::new();
+        if limit == Some(0) {
+            return solutions;
+        }
+
+        self.search(|solution| {
+            solutions.push(solution.to_vec());
+            limit.is_none_or(|limit| solutions.len() < limit)
+        });
+
+        solutions
+    }
+
+    pub fn count_solutions(&mut self, limit: Option<usize>) -> usize {
+        let mut count = 0;
+        if limit == Some(0) {
+            return count;
+        }
+
+        self.search(|_| {
+            count += 1;
+            limit.is_none_or(|limit| count < limit)
+        });
+
+        count
+    }
+
+    // Calls `visit` with the sorted row indices of every solution until it
+    // returns false. The matrix is fully restored afterwards either way.
+    pub fn search<F: FnMut(&[usize]) -> bool>(&mut self, mut visit: F) {
+        let mut partial = Vec::new();
+        self.search_helper(&mut partial, &mut visit);
+    }
+
+    fn search_helper<F: FnMut(&[usize]) -> bool>(&mut self, partial: &mut Vec<usize>, visit: &mut F) -> bool {
+        if self.right[Self::ROOT] == Self::ROOT {
+            let mut solution = partial.clone();
+            solution.sort_unstable();
+            return visit(&solution);
+        }
+
+        // Branch on the primary column with the fewest remaining rows
+        let mut header = self.right[Self::ROOT];
+        let mut c = self.right[header];
+        while c != Self::ROOT {
+            if self.size[c] < self.size[header] {
+                header = c;
+            }
+            c = self.right[c];
+        }
+
+        if self.size[header] == 0 {
+            return true;
+        }
+
+        self.cover(header);
+        let mut keep_going = true;
+        let mut r = self.down[header];
+        while r != header && keep_going {
+            partial.push(self.row[r]);
+            let mut j = self.right[r];
+            while j != r {
+                self.select(self.column[j]);
+                j = self.right[j];
+            }
+
+            keep_going = self.search_helper(partial, visit);
+
+            let mut j = self.left[r];
+            while j != r {
+                self.deselect(self.column[j]);
+                j = self.left[j];
+            }
+            partial.pop();
+            r = self.down[r];
+        }
+        self.uncover(header);
+
+        keep_going
+    }
+
+    fn select(&mut self, header: usize) {
+        self.remaining[header] -= 1;
+        if self.remaining[header] == 0 {
+            self.cover(header);
+        }
+    }
+
+    fn deselect(&mut self, header: usize) {
+        if self.remaining[header] == 0 {
+            self.uncover(header);
+        }
+        self.remaining[header] += 1;
+    }
+
+    fn cover(&mut self, header: usize) {
+        let (l, r) = (self.left[header], self.right[header]);
+        self.right[l] = r;
+        self.left[r] = l;
+        let mut i = self.down[header];
+        while i != header {
+            let mut j = self.right[i];
+            while j != i {
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = d;
+                self.up[d] = u;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut i = self.up[header];
+        while i != header {
+            let mut j = self.left[i];
+            while j != i {
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = j;
+                self.up[d] = j;
+                self.size[self.column[j]] += 1;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        let (l, r) = (self.left[header], self.right[header]);
+        self.right[l] = header;
+        self.left[r] = header;
+    }
+}
+
+#[test]
+fn test_dlx_knuth_example() {
+    // The example matrix from Knuth's "Dancing Links" paper
+    let mut dlx = Dlx::new(7, 0);
+    dlx.add_row(&[2, 4, 5]);
+    dlx.add_row(&[0, 3, 6]);
+    dlx.add_row(&[1, 2, 5]);
+    dlx.add_row(&[0, 3]);
+    dlx.add_row(&[1, 6]);
+    dlx.add_row(&[3, 4, 6]);
+    assert_eq!(dlx.solve_first(), Some(vec![0, 3, 4]));
+    assert_eq!(dlx.count_solutions(None), 1);
+}
+
+#[test]
+fn test_dlx_all_solutions_and_limit() {
+    // Two columns, each coverable by two rows or by one row covering both
+    let mut dlx = Dlx::new(2, 0);
+    dlx.add_row(&[0]);
+    dlx.add_row(&[0]);
+    dlx.add_row(&[1]);
+    dlx.add_row(&[1]);
+    dlx.add_row(&[0, 1]);
+    assert_eq!(dlx.count_solutions(None), 5);
+    assert_eq!(dlx.count_solutions(Some(3)), 3);
+    assert_eq!(dlx.solve_all(Some(2)).len(), 2);
+    assert_eq!(dlx.solve_all(None), vec![vec![0, 2], vec![0, 3], vec![1, 2], vec![1, 3], vec![4]]);
+}
+
+#[test]
+fn test_dlx_secondary_columns() {
+    // Rows 0 and 1 both touch secondary column 2, so they can't be combined
+    let mut dlx = Dlx::new(2, 1);
+    dlx.add_row(&[0, 2]);
+    dlx.add_row(&[1, 2]);
+    dlx.add_row(&[1]);
+    assert_eq!(dlx.solve_all(None), vec![vec![0, 2]]);
+
+    // With a capacity of two both rows fit
+    let mut dlx = Dlx::with_capacities(2, &[2]);
+    dlx.add_row(&[0, 2]);
+    dlx.add_row(&[1, 2]);
+    dlx.add_row(&[1]);
+    assert_eq!(dlx.solve_all(None), vec![vec![0, 1], vec![0, 2]]);
+}
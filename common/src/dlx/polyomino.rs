@@ -0,0 +1,348 @@
+use std::collections::HashSet;
+
+use super::Dlx;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Polyomino {
+    cells: Vec<(i32, i32)>, // (x, y) offsets, normalized so the minimum x and y are 0
+}
+
+impl Polyomino {
+    pub fn new(cells: &[(i32, i32)]) -> Self {
+        assert!(!cells.is_empty(), "A polyomino needs at least one cell");
+        let min_x = cells.iter().map(|(x, _)| *x).min().unwrap();
+        let min_y = cells.iter().map(|(_, y)| *y).min().unwrap();
+        let mut cells = cells
+            .iter()
+            .map(|&(x, y)| (x - min_x, y - min_y))
+            .collect::<Vec<(i32, i32)>>();
+        cells.sort_unstable();
+        cells.dedup();
+
+        Polyomino { cells }
+    }
+
+    // Parses a shape drawn with '#' for occupied cells, e.g. ["##.", ".##"]
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let cells = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.chars()
+                    .enumerate()
+                    .filter(|(_, c)| *c == '#')
+                    .map(move |(x, _)| (x as i32, y as i32))
+            })
+            .collect::<Vec<(i32, i32)>>();
+
+        Polyomino::new(&cells)
+    }
+
+    pub fn cells(&self) -> &[(i32, i32)] {
+        &self.cells
+    }
+
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.iter().map(|(x, _)| *x).max().unwrap() as usize + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.iter().map(|(_, y)| *y).max().unwrap() as usize + 1
+    }
+
+    pub fn rotate(&self) -> Self {
+        let cells = self.cells.iter().map(|&(x, y)| (-y, x)).collect::<Vec<(i32, i32)>>();
+        Polyomino::new(&cells)
+    }
+
+    pub fn reflect(&self) -> Self {
+        let cells = self.cells.iter().map(|&(x, y)| (-x, y)).collect::<Vec<(i32, i32)>>();
+        Polyomino::new(&cells)
+    }
+
+    // The distinct quarter-turn rotations of the shape
+    pub fn rotations(&self) -> Vec<Polyomino> {
+        let mut rotations = Vec::new();
+        let mut shape = self.clone();
+        for _ in 0..4 {
+            if !rotations.contains(&shape) {
+                rotations.push(shape.clone());
+            }
+            shape = shape.rotate();
+        }
+
+        rotations
+    }
+
+    // The distinct rotations and reflections of the shape
+    pub fn orientations(&self) -> Vec<Polyomino> {
+        let mut orientations = self.rotations();
+        for shape in self.reflect().rotations() {
+            if !orientations.contains(&shape) {
+                orientations.push(shape);
+            }
+        }
+
+        orientations
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    blocked: HashSet<(i32, i32)>,
+}
+
+impl Board {
+    pub fn new(width: usize, height: usize) -> Self {
+        Board {
+            width,
+            height,
+            blocked: HashSet::new(),
+        }
+    }
+
+    pub fn block(&mut self, x: i32, y: i32) {
+        self.blocked.insert((x, y));
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    pub fn is_free(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y) && !self.blocked.contains(&(x, y))
+    }
+
+    pub fn free_area(&self) -> usize {
+        let blocked = self.blocked.iter().filter(|&&(x, y)| self.in_bounds(x, y)).count();
+        self.width * self.height - blocked
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    pub piece: usize, // index of the piece in the order it was added
+    pub cells: Vec<(i32, i32)>,
+}
+
+// Packs a multiset of polyominoes onto a board. Without gaps the pieces must
+// tile every free cell; with gaps the spare cells may stay empty but every
+// piece must still be placed.
+#[derive(Debug, Clone)]
+pub struct Packing {
+    board: Board,
+    pieces: Vec<(Polyomino, usize)>,
+    allow_gaps: bool,
+    allow_reflections: bool,
+}
+
+impl Packing {
+    pub fn new(board: Board) -> Self {
+        Packing {
+            board,
+            pieces: Vec::new(),
+            allow_gaps: false,
+            allow_reflections: true,
+        }
+    }
+
+    pub fn with_gaps(board: Board) -> Self {
+        Packing {
+            allow_gaps: true,
+            ..Packing::new(board)
+        }
+    }
+
+    pub fn set_reflections(&mut self, allow_reflections: bool) {
+        self.allow_reflections = allow_reflections;
+    }
+
+    // Returns the piece index used in `Placement::piece`
+    pub fn add_piece(&mut self, shape: Polyomino, count: usize) -> usize {
+        self.pieces.push((shape, count));
+        self.pieces.len() - 1
+    }
+
+    pub fn pieces_area(&self) -> usize {
+        self.pieces.iter().map(|(shape, count)| shape.area() * count).sum()
+    }
+
+    // Builds the exact cover matrix together with the placement each row
+    // stands for. Free board cells are the primary columns, every piece gets a
+    // secondary column with its count as capacity, and with gaps allowed each
+    // cell also gets a one-cell filler row sharing a column whose capacity is
+    // the spare area. Returns None when the areas alone rule out a packing.
+    pub fn to_dlx(&self) -> Option<(Dlx, Vec<Option<Placement>>)> {
+        let free_area = self.board.free_area();
+        let pieces_area = self.pieces_area();
+        if pieces_area > free_area || (!self.allow_gaps && pieces_area < free_area) {
+            return None;
+        }
+
+        let mut cell_columns = vec![vec![usize::MAX; self.board.width]; self.board.height];
+        let mut free_cells = Vec::with_capacity(free_area);
+        for (y, row) in cell_columns.iter_mut().enumerate() {
+            for (x, column) in row.iter_mut().enumerate() {
+                if self.board.is_free(x as i32, y as i32) {
+                    *column = free_cells.len();
+                    free_cells.push((x as i32, y as i32));
+                }
+            }
+        }
+
+        let mut capacities = self.pieces.iter().map(|(_, count)| *count).collect::<Vec<usize>>();
+        if self.allow_gaps {
+            capacities.push(free_area - pieces_area);
+        }
+
+        let mut dlx = Dlx::with_capacities(free_cells.len(), &capacities);
+        let mut placements = Vec::new();
+        for (piece, (shape, count)) in self.pieces.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+
+            let orientations = if self.allow_reflections {
+                shape.orientations()
+            }
+            else {
+                shape.rotations()
+            };
+
+            for orientation in orientations {
+                if orientation.width() > self.board.width || orientation.height() > self.board.height {
+                    continue;
+                }
+
+                for oy in 0..=(self.board.height - orientation.height()) as i32 {
+                    for ox in 0..=(self.board.width - orientation.width()) as i32 {
+                        let cells = orientation
+                            .cells()
+                            .iter()
+                            .map(|&(x, y)| (x + ox, y + oy))
+                            .collect::<Vec<(i32, i32)>>();
+                        if !cells.iter().all(|&(x, y)| self.board.is_free(x, y)) {
+                            continue;
+                        }
+
+                        let mut columns = cells
+                            .iter()
+                            .map(|&(x, y)| cell_columns[y as usize][x as usize])
+                            .collect::<Vec<usize>>();
+                        columns.push(free_cells.len() + piece);
+                        dlx.add_row(&columns);
+                        placements.push(Some(Placement { piece, cells }));
+                    }
+                }
+            }
+        }
+
+        if self.allow_gaps {
+            let gap_column = free_cells.len() + self.pieces.len();
+            for cell in 0..free_cells.len() {
+                dlx.add_row(&[cell, gap_column]);
+                placements.push(None);
+            }
+        }
+
+        Some((dlx, placements))
+    }
+
+    pub fn solve(&self) -> Option<Vec<Placement>> {
+        let (mut dlx, placements) = self.to_dlx()?;
+        let solution = dlx.solve_first()?;
+        Some(Self::to_placements(&solution, &placements))
+    }
+
+    pub fn solve_all(&self, limit: Option<usize>) -> Vec<Vec<Placement>> {
+        let Some((mut dlx, placements)) = self.to_dlx() else {
+            return Vec::new();
+        };
+
+        dlx.solve_all(limit)
+            .iter()
+            .map(|solution| Self::to_placements(solution, &placements))
+            .collect()
+    }
+
+    pub fn count_solutions(&self, limit: Option<usize>) -> usize {
+        match self.to_dlx() {
+            Some((mut dlx, _)) => dlx.count_solutions(limit),
+            None => 0,
+        }
+    }
+
+    fn to_placements(solution: &[usize], placements: &[Option<Placement>]) -> Vec<Placement> {
+        solution
+            .iter()
+            .filter_map(|&row| placements[row].clone())
+            .collect()
+    }
+}
+
+#[test]
+fn test_polyomino_orientations() {
+    let square = Polyomino::from_rows(&["##", "##"]);
+    assert_eq!(square.orientations().len(), 1);
+
+    let l_tromino = Polyomino::from_rows(&["#.", "##"]);
+    assert_eq!(l_tromino.orientations().len(), 4);
+
+    let s_tetromino = Polyomino::from_rows(&[".##", "##."]);
+    assert_eq!(s_tetromino.rotations().len(), 2);
+    assert_eq!(s_tetromino.orientations().len(), 4);
+
+    let f_pentomino = Polyomino::from_rows(&[".##", "##.", ".#."]);
+    assert_eq!(f_pentomino.orientations().len(), 8);
+}
+
+#[test]
+fn test_packing_counts_domino_tilings() {
+    // A 2xn board has Fibonacci(n + 1) domino tilings
+    let mut packing = Packing::new(Board::new(8, 2));
+    packing.add_piece(Polyomino::from_rows(&["##"]), 8);
+    assert_eq!(packing.count_solutions(None), 34);
+    assert_eq!(packing.count_solutions(Some(10)), 10);
+}
+
+#[test]
+fn test_packing_with_gaps_and_blocked_cells() {
+    let mut board = Board::new(3, 3);
+    board.block(1, 1);
+    let mut packing = Packing::new(board.clone());
+    packing.add_piece(Polyomino::from_rows(&["###"]), 2);
+    packing.add_piece(Polyomino::from_rows(&["#", "#"]), 1);
+    // The areas match but the domino can't bridge the two leftover side cells
+    assert_eq!(packing.pieces_area(), board.free_area());
+    assert!(packing.solve().is_none());
+
+    let mut packing = Packing::new(board.clone());
+    packing.add_piece(Polyomino::from_rows(&["###"]), 2);
+    assert!(packing.solve().is_none());
+
+    let mut packing = Packing::with_gaps(board);
+    packing.add_piece(Polyomino::from_rows(&["###"]), 2);
+    let placements = packing.solve().unwrap();
+    assert_eq!(placements.len(), 2);
+    let mut covered = HashSet::new();
+    for placement in &placements {
+        for &cell in &placement.cells {
+            assert_ne!(cell, (1, 1));
+            assert!(covered.insert(cell));
+        }
+    }
+}
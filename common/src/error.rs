@@ -1,11 +1,51 @@
 use thiserror::Error;
 
+use crate::automaton::AutomatonError;
+use crate::circuit::CircuitError;
+use crate::combat::CombatError;
+use crate::grammar::GrammarError;
+use crate::json_stream::JsonStreamError;
+use crate::look_and_say::LookAndSayError;
+use crate::password::PasswordError;
+use crate::polygon::PolygonError;
+use crate::query::QueryError;
+use crate::race::RaceError;
+use crate::recipe::RecipeError;
+use crate::subset_sum::SubsetSumError;
+use crate::vm::VmError;
+
 #[derive(Debug, Error)]
 pub enum PuzzleError {
     #[error("IO Error: {0}")]
     Io(#[from] std::io::Error),
     #[error("JSON Error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("Automaton Error: {0}")]
+    Automaton(#[from] AutomatonError),
+    #[error("Circuit Error: {0}")]
+    Circuit(#[from] CircuitError),
+    #[error("Combat Error: {0}")]
+    Combat(#[from] CombatError),
+    #[error("Grammar Error: {0}")]
+    Grammar(#[from] GrammarError),
+    #[error("JSON Stream Error: {0}")]
+    JsonStream(#[from] JsonStreamError),
+    #[error("Look-and-say Error: {0}")]
+    LookAndSay(#[from] LookAndSayError),
+    #[error("Password Error: {0}")]
+    Password(#[from] PasswordError),
+    #[error("Polygon Error: {0}")]
+    Polygon(#[from] PolygonError),
+    #[error("Query Error: {0}")]
+    Query(#[from] QueryError),
+    #[error("Race Error: {0}")]
+    Race(#[from] RaceError),
+    #[error("Recipe Error: {0}")]
+    Recipe(#[from] RecipeError),
+    #[error("Subset Sum Error: {0}")]
+    SubsetSum(#[from] SubsetSumError),
+    #[error("VM Error: {0}")]
+    Vm(#[from] VmError),
     #[error("{0}")]
     Custom(&'static str),
 }
\ No newline at end of file
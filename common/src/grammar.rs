@@ -0,0 +1,285 @@
+use std::collections::{HashMap, HashSet};
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum GrammarError {
+    #[error("Invalid rule: {0}")]
+    Parse(String),
+    #[error("Rule '{0}' replaces a symbol with nothing")]
+    EmptyReplacement(String),
+}
+
+// Splits a molecule into elements: an uppercase letter followed by any
+// lowercase ones. A lowercase run with nothing before it, like "e", is an
+// element of its own.
+pub fn tokenize(molecule: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    for (i, c) in molecule.char_indices().skip(1) {
+        if c.is_uppercase() {
+            tokens.push(&molecule[start..i]);
+            start = i;
+        }
+    }
+    if start < molecule.len() {
+        tokens.push(&molecule[start..]);
+    }
+
+    tokens
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub from: usize,
+    pub to: Vec<usize>,
+}
+
+// Replacement rules over interned element symbols
+#[derive(Debug, Clone, Default)]
+pub struct RewriteSystem {
+    symbols: Vec<String>,
+    ids: HashMap<String, usize>,
+    rules: Vec<Rule>,
+}
+
+impl RewriteSystem {
+    pub fn new() -> Self {
+        RewriteSystem::default()
+    }
+
+    // One "Al => ThF" rule per non-empty line
+    pub fn parse(input: &str) -> Result<Self, GrammarError> {
+        let mut system = RewriteSystem::new();
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            let (from, to) = line.split_once("=>").ok_or_else(|| GrammarError::Parse(line.to_string()))?;
+            system.add_rule(from.trim(), to.trim())?;
+        }
+
+        Ok(system)
+    }
+
+    pub fn add_rule(&mut self, from: &str, to: &str) -> Result<(), GrammarError> {
+        if tokenize(from).len() != 1 {
+            return Err(GrammarError::Parse(format!("{} => {}", from, to)));
+        }
+        if to.is_empty() {
+            return Err(GrammarError::EmptyReplacement(from.to_string()));
+        }
+
+        let from = self.intern(from);
+        let to = self.encode(to);
+        self.rules.push(Rule { from, to });
+        Ok(())
+    }
+
+    fn intern(&mut self, symbol: &str) -> usize {
+        if let Some(&id) = self.ids.get(symbol) {
+            return id;
+        }
+
+        self.symbols.push(symbol.to_string());
+        self.ids.insert(symbol.to_string(), self.symbols.len() - 1);
+        self.symbols.len() - 1
+    }
+
+    pub fn symbol(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn symbol_name(&self, symbol: usize) -> &str {
+        &self.symbols[symbol]
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    // Tokenizes a molecule, registering elements no rule mentions
+    pub fn encode(&mut self, molecule: &str) -> Vec<usize> {
+        tokenize(molecule).into_iter().map(|token| self.intern(token)).collect()
+    }
+
+    pub fn decode(&self, molecule: &[usize]) -> String {
+        molecule.iter().map(|&symbol| self.symbols[symbol].as_str()).collect()
+    }
+
+    // Every distinct molecule one replacement away from `molecule`
+    pub fn neighbors(&self, molecule: &[usize]) -> HashSet<Vec<usize>> {
+        let mut neighbors = HashSet::new();
+        for (i, &symbol) in molecule.iter().enumerate() {
+            for rule in self.rules.iter().filter(|rule| rule.from == symbol) {
+                let mut next = Vec::with_capacity(molecule.len() + rule.to.len() - 1);
+                next.extend_from_slice(&molecule[..i]);
+                next.extend_from_slice(&rule.to);
+                next.extend_from_slice(&molecule[i + 1..]);
+                neighbors.insert(next);
+            }
+        }
+
+        neighbors
+    }
+
+    // Fewest replacements turning `start` into `target`, or None if the
+    // grammar cannot derive it. A weighted CYK parse: every rule
+    // A => B1 B2 .. Bk is split into a chain of binary rules through helper
+    // symbols, where only the first link costs a step, and unit rules A => B
+    // are relaxed within each cell until nothing improves. Since no rule
+    // shrinks a molecule, every binary split works on strictly shorter spans.
+    pub fn min_derivation(&self, start: usize, target: &[usize]) -> Option<usize> {
+        let n = target.len();
+        if n == 0 {
+            return None;
+        }
+
+        // (left, right) -> [(parent, cost)], helper symbols follow the real ones
+        let mut binary: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+        let mut unit: Vec<(usize, usize)> = Vec::new();
+        let mut next_helper = self.symbols.len();
+        for rule in &self.rules {
+            match rule.to.as_slice() {
+                [single] => unit.push((rule.from, *single)),
+                [first, rest @ ..] => {
+                    let mut parent = rule.from;
+                    let mut cost = 1;
+                    let mut left = *first;
+                    for (k, &symbol) in rest.iter().enumerate() {
+                        let right = if k + 1 == rest.len() {
+                            symbol
+                        }
+                        else {
+                            next_helper += 1;
+                            next_helper - 1
+                        };
+                        binary.entry((left, right)).or_default().push((parent, cost));
+                        parent = right;
+                        cost = 0;
+                        left = symbol;
+                    }
+                }
+                [] => (),
+            }
+        }
+
+        // cells[i][len - 1] holds the cheapest derivation of target[i..i + len]
+        // from each symbol that can produce it
+        let mut cells: Vec<Vec<HashMap<usize, usize>>> = vec![Vec::with_capacity(n); n];
+        for len in 1..=n {
+            for i in 0..=n - len {
+                let mut cell: HashMap<usize, usize> = HashMap::new();
+                if len == 1 {
+                    cell.insert(target[i], 0);
+                }
+                for split in 1..len {
+                    let (left_cell, right_cell) = (&cells[i][split - 1], &cells[i + split][len - split - 1]);
+                    for (&left, &left_cost) in left_cell {
+                        for (&right, &right_cost) in right_cell {
+                            let Some(parents) = binary.get(&(left, right)) else {
+                                continue;
+                            };
+                            for &(parent, cost) in parents {
+                                let total = left_cost + right_cost + cost;
+                                let entry = cell.entry(parent).or_insert(usize::MAX);
+                                *entry = (*entry).min(total);
+                            }
+                        }
+                    }
+                }
+
+                let mut changed = true;
+                while changed {
+                    changed = false;
+                    for &(parent, child) in &unit {
+                        if let Some(&child_cost) = cell.get(&child) {
+                            let entry = cell.entry(parent).or_insert(usize::MAX);
+                            if child_cost + 1 < *entry {
+                                *entry = child_cost + 1;
+                                changed = true;
+                            }
+                        }
+                    }
+                }
+
+                cells[i].push(cell);
+            }
+        }
+
+        cells[0][n - 1].get(&start).copied()
+    }
+}
+
+#[cfg(test)]
+fn breadth_first_derivation(system: &RewriteSystem, start: usize, target: &[usize]) -> Option<usize> {
+    // Molecules never shrink, so anything longer than the target is a dead end
+    let mut seen = HashSet::from([vec![start]]);
+    let mut frontier = vec![vec![start]];
+    for steps in 0.. {
+        if frontier.is_empty() {
+            return None;
+        }
+        if frontier.iter().any(|molecule| molecule == target) {
+            return Some(steps);
+        }
+
+        frontier = frontier
+            .iter()
+            .flat_map(|molecule| system.neighbors(molecule))
+            .filter(|next| next.len() <= target.len() && seen.insert(next.clone()))
+            .collect();
+    }
+
+    None
+}
+
+#[test]
+fn test_tokenize() {
+    assert_eq!(tokenize("CRnCaSiRnBSiRnFAr"), vec!["C", "Rn", "Ca", "Si", "Rn", "B", "Si", "Rn", "F", "Ar"]);
+    assert_eq!(tokenize("e"), vec!["e"]);
+    assert_eq!(tokenize("eHO"), vec!["e", "H", "O"]);
+    assert!(tokenize("").is_empty());
+}
+
+#[test]
+fn test_rewrite_puzzle_examples() {
+    let mut system = RewriteSystem::parse("e => H\ne => O\nH => HO\nH => OH\nO => HH").unwrap();
+    let e = system.symbol("e").unwrap();
+
+    let hoh = system.encode("HOH");
+    let neighbors = system.neighbors(&hoh);
+    assert_eq!(neighbors.len(), 4);
+    assert!(neighbors.contains(&system.encode("HOOH")));
+    let hohoho = system.encode("HOHOHO");
+    assert_eq!(system.neighbors(&hohoho).len(), 7);
+
+    assert_eq!(system.min_derivation(e, &hoh), Some(3));
+    assert_eq!(system.min_derivation(e, &hohoho), Some(6));
+    assert_eq!(system.decode(&hohoho), "HOHOHO");
+
+    let unreachable = system.encode("Xe");
+    assert_eq!(system.min_derivation(e, &unreachable), None);
+}
+
+#[test]
+fn test_min_derivation_matches_breadth_first_search() {
+    // Long rules, unit cycles and molecules with several parses
+    let mut system = RewriteSystem::parse(
+        "e => AB\ne => CaRnAr\nA => B\nB => A\nB => CaCa\nCa => ARnB\nRn => BAr\nAr => Ca\nA => RnYCaAr",
+    )
+    .unwrap();
+    let e = system.symbol("e").unwrap();
+    let targets = [
+        "AB", "BA", "CaCaB", "ARnBCa", "CaRnAr", "CaBArCa", "ARnBArB", "CaARnBArCaCa", "RnYCaArCaCa", "ABAr", "BB",
+    ];
+    for target in targets {
+        let target = system.encode(target);
+        assert_eq!(
+            system.min_derivation(e, &target),
+            breadth_first_derivation(&system, e, &target),
+            "{}",
+            system.decode(&target)
+        );
+    }
+
+    assert_eq!(RewriteSystem::parse("e => ").unwrap_err(), GrammarError::EmptyReplacement("e".to_string()));
+    assert!(matches!(RewriteSystem::parse("HO => H"), Err(GrammarError::Parse(_))));
+}
@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use num_traits::PrimInt;
+
+// A set of integers stored as disjoint, non-adjacent inclusive ranges keyed by
+// their start, so overlapping or touching insertions are merged eagerly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    ranges: BTreeMap<T, T>,
+}
+
+impl<T: PrimInt> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet::new()
+    }
+}
+
+impl<T: PrimInt> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (mut start, mut end) = range.into_inner();
+        if start > end {
+            return;
+        }
+
+        // A range ending right before `start` touches the new one
+        let lookup = start.checked_sub(&T::one()).unwrap_or(start);
+        if let Some((&prev_start, &prev_end)) = self.ranges.range(..=lookup).next_back()
+            && prev_end.checked_add(&T::one()).is_none_or(|next| next >= start)
+        {
+            start = prev_start;
+            end = end.max(prev_end);
+        }
+
+        let reach = end.checked_add(&T::one()).unwrap_or(end);
+        let absorbed = self
+            .ranges
+            .range(start..=reach)
+            .map(|(&s, &e)| (s, e))
+            .collect::<Vec<(T, T)>>();
+        for (s, e) in absorbed {
+            self.ranges.remove(&s);
+            end = end.max(e);
+        }
+
+        self.ranges.insert(start, end);
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if start > end {
+            return;
+        }
+
+        let mut overlapping = self
+            .ranges
+            .range(start..=end)
+            .map(|(&s, &e)| (s, e))
+            .collect::<Vec<(T, T)>>();
+        if let Some((&s, &e)) = self.ranges.range(..start).next_back()
+            && e >= start
+        {
+            overlapping.push((s, e));
+        }
+
+        for (s, e) in overlapping {
+            self.ranges.remove(&s);
+            if s < start {
+                self.ranges.insert(s, start - T::one());
+            }
+            if e > end {
+                self.ranges.insert(end + T::one(), e);
+            }
+        }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.ranges
+            .range(..=value)
+            .next_back()
+            .is_some_and(|(_, &end)| value <= end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    // Number of disjoint ranges, not the number of covered values
+    pub fn num_ranges(&self) -> usize {
+        self.ranges.len()
+    }
+
+    // Total count of covered values. Saturates at `T::max_value()` if the
+    // count doesn't fit in `T`.
+    pub fn covered_len(&self) -> T {
+        self.ranges.iter().fold(T::zero(), |total, (&start, &end)| {
+            let len = end.checked_sub(&start).map_or(T::max_value(), |len| len.saturating_add(T::one()));
+            total.saturating_add(len)
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..=end)
+    }
+
+    // The uncovered ranges between the first and last covered values
+    pub fn gaps(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges
+            .iter()
+            .zip(self.ranges.iter().skip(1))
+            .map(|((_, &end), (&next_start, _))| (end + T::one())..=(next_start - T::one()))
+    }
+
+    // The uncovered ranges inside `bounds`, including any before the first or
+    // after the last covered value
+    pub fn gaps_in(&self, bounds: RangeInclusive<T>) -> IntervalSet<T> {
+        let mut gaps = IntervalSet::new();
+        gaps.insert(bounds);
+        gaps.difference(self)
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.insert(range);
+        }
+
+        result
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = IntervalSet::new();
+        let mut a = self.ranges.iter().peekable();
+        let mut b = other.ranges.iter().peekable();
+        while let (Some(&(&a_start, &a_end)), Some(&(&b_start, &b_end))) = (a.peek(), b.peek()) {
+            let start = a_start.max(b_start);
+            let end = a_end.min(b_end);
+            if start <= end {
+                result.ranges.insert(start, end);
+            }
+
+            if a_end < b_end {
+                a.next();
+            }
+            else {
+                b.next();
+            }
+        }
+
+        result
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.remove(range);
+        }
+
+        result
+    }
+}
+
+impl<T: PrimInt> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+
+        set
+    }
+}
+
+#[test]
+fn test_interval_set_merges_on_insert() {
+    let mut set = IntervalSet::new();
+    set.insert(3..=5);
+    set.insert(10..=14);
+    set.insert(16..=20);
+    set.insert(12..=18);
+    set.insert(6..=6);
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![3..=6, 10..=20]);
+    assert_eq!(set.covered_len(), 15);
+    assert!(set.contains(3) && set.contains(6) && set.contains(17));
+    assert!(!set.contains(2) && !set.contains(7) && !set.contains(21));
+    assert_eq!(set.gaps().collect::<Vec<_>>(), vec![7..=9]);
+    assert_eq!(set.gaps_in(0..=25).iter().collect::<Vec<_>>(), vec![0..=2, 7..=9, 21..=25]);
+}
+
+#[test]
+fn test_interval_set_operations() {
+    let a = [1..=5, 10..=15].into_iter().collect::<IntervalSet<i64>>();
+    let b = [4..=11, 20..=22].into_iter().collect::<IntervalSet<i64>>();
+    assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), vec![1..=15, 20..=22]);
+    assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), vec![4..=5, 10..=11]);
+    assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), vec![1..=3, 12..=15]);
+    assert_eq!(b.difference(&a).iter().collect::<Vec<_>>(), vec![6..=9, 20..=22]);
+}
+
+#[test]
+fn test_interval_set_type_bounds() {
+    let mut set = IntervalSet::<u8>::new();
+    set.insert(0..=10);
+    set.insert(250..=255);
+    set.insert(11..=249);
+    assert_eq!(set.num_ranges(), 1);
+    assert_eq!(set.covered_len(), u8::MAX);
+    set.remove(0..=0);
+    set.remove(255..=255);
+    assert_eq!(set.iter().collect::<Vec<_>>(), vec![1..=254]);
+
+    // Wider than the type itself can count
+    let full = [i64::MIN..=i64::MAX].into_iter().collect::<IntervalSet<i64>>();
+    assert_eq!(full.covered_len(), i64::MAX);
+    let halves = [i8::MIN..=-1, 0..=i8::MAX].into_iter().collect::<IntervalSet<i8>>();
+    assert_eq!(halves.covered_len(), i8::MAX);
+}
@@ -0,0 +1,663 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JsonStreamError {
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Unexpected '{found}' at byte {offset}")]
+    Unexpected { offset: u64, found: char },
+    #[error("Unexpected end of input")]
+    UnexpectedEnd,
+    #[error("Invalid escape sequence at byte {0}")]
+    InvalidEscape(u64),
+}
+
+// Callbacks for each piece of a JSON document, in document order. Keys come
+// right before the value they name.
+pub trait JsonVisitor {
+    fn start_object(&mut self) {}
+    fn end_object(&mut self) {}
+    fn start_array(&mut self) {}
+    fn end_array(&mut self) {}
+    fn key(&mut self, _key: &str) {}
+    fn string(&mut self, _value: &str) {}
+    fn number(&mut self, _text: &str) {} // the number exactly as written
+    fn boolean(&mut self, _value: bool) {}
+    fn null(&mut self) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Object,
+    Array,
+}
+
+struct Parser<R: Read> {
+    reader: BufReader<R>,
+    offset: u64,
+    text: String, // reused for strings and numbers
+}
+
+impl<R: Read> Parser<R> {
+    fn peek(&mut self) -> Result<Option<u8>, JsonStreamError> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    fn bump(&mut self) -> Result<u8, JsonStreamError> {
+        let byte = self.peek()?.ok_or(JsonStreamError::UnexpectedEnd)?;
+        self.reader.consume(1);
+        self.offset += 1;
+        Ok(byte)
+    }
+
+    fn unexpected(&self, byte: u8) -> JsonStreamError {
+        JsonStreamError::Unexpected {
+            offset: self.offset - 1,
+            found: byte as char,
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), JsonStreamError> {
+        match self.bump()? {
+            byte if byte == expected => Ok(()),
+            byte => Err(self.unexpected(byte)),
+        }
+    }
+
+    // Skips whitespace and returns the next byte without consuming it
+    fn next_token(&mut self) -> Result<Option<u8>, JsonStreamError> {
+        while let Some(byte) = self.peek()? {
+            if !matches!(byte, b' ' | b'\t' | b'\n' | b'\r') {
+                return Ok(Some(byte));
+            }
+            self.bump()?;
+        }
+
+        Ok(None)
+    }
+
+    fn hex_escape(&mut self) -> Result<u32, JsonStreamError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let digit = (self.bump()? as char).to_digit(16).ok_or(JsonStreamError::InvalidEscape(self.offset - 1))?;
+            code = code * 16 + digit;
+        }
+
+        Ok(code)
+    }
+
+    // Reads a string into `self.text`, the opening quote already consumed
+    fn string(&mut self) -> Result<(), JsonStreamError> {
+        let mut bytes = std::mem::take(&mut self.text).into_bytes();
+        bytes.clear();
+        loop {
+            match self.bump()? {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = match self.bump()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let start = self.offset - 2;
+                            let mut code = self.hex_escape()?;
+                            if (0xD800..0xDC00).contains(&code) {
+                                self.expect(b'\\')?;
+                                self.expect(b'u')?;
+                                let low = self.hex_escape()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(JsonStreamError::InvalidEscape(start));
+                                }
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+                            char::from_u32(code).ok_or(JsonStreamError::InvalidEscape(start))?
+                        }
+                        byte => return Err(self.unexpected(byte)),
+                    };
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte if byte < 0x20 => return Err(self.unexpected(byte)),
+                byte => bytes.push(byte),
+            }
+        }
+
+        self.text = String::from_utf8(bytes).map_err(|_| JsonStreamError::Unexpected {
+            offset: self.offset - 1,
+            found: '\u{FFFD}',
+        })?;
+        Ok(())
+    }
+
+    fn push_next(&mut self) -> Result<(), JsonStreamError> {
+        let byte = self.bump()?;
+        self.text.push(byte as char);
+        Ok(())
+    }
+
+    fn digits(&mut self) -> Result<(), JsonStreamError> {
+        let first = self.bump()?;
+        if !first.is_ascii_digit() {
+            return Err(self.unexpected(first));
+        }
+        self.text.push(first as char);
+        while self.peek()?.is_some_and(|byte| byte.is_ascii_digit()) {
+            self.push_next()?;
+        }
+
+        Ok(())
+    }
+
+    // Reads a number into `self.text`, checking it against the JSON grammar
+    fn number(&mut self) -> Result<(), JsonStreamError> {
+        self.text.clear();
+        if self.peek()? == Some(b'-') {
+            self.push_next()?;
+        }
+        if self.peek()? == Some(b'0') {
+            self.push_next()?;
+        }
+        else {
+            self.digits()?;
+        }
+        if self.peek()? == Some(b'.') {
+            self.push_next()?;
+            self.digits()?;
+        }
+        if let Some(b'e' | b'E') = self.peek()? {
+            self.push_next()?;
+            if let Some(b'+' | b'-') = self.peek()? {
+                self.push_next()?;
+            }
+            self.digits()?;
+        }
+
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &[u8]) -> Result<(), JsonStreamError> {
+        word.iter().try_for_each(|&byte| self.expect(byte))
+    }
+
+    // Reads an object key and the colon after it
+    fn key<V: JsonVisitor>(&mut self, visitor: &mut V) -> Result<(), JsonStreamError> {
+        match self.next_token()? {
+            Some(b'"') => self.bump()?,
+            Some(_) => {
+                let byte = self.bump()?;
+                return Err(self.unexpected(byte));
+            }
+            None => return Err(JsonStreamError::UnexpectedEnd),
+        };
+        self.string()?;
+        visitor.key(&self.text);
+        self.next_token()?;
+        self.expect(b':')
+    }
+}
+
+// Parses a whole document from `reader`, reporting it to `visitor` as it
+// goes. Nesting is tracked on an explicit stack, so memory only grows with
+// the depth of the document and the longest string in it.
+pub fn visit<R: Read, V: JsonVisitor>(reader: R, visitor: &mut V) -> Result<(), JsonStreamError> {
+    let mut parser = Parser {
+        reader: BufReader::new(reader),
+        offset: 0,
+        text: String::new(),
+    };
+    let mut stack = Vec::new();
+
+    'value: loop {
+        match parser.next_token()?.ok_or(JsonStreamError::UnexpectedEnd)? {
+            b'{' => {
+                parser.bump()?;
+                visitor.start_object();
+                if parser.next_token()? == Some(b'}') {
+                    parser.bump()?;
+                    visitor.end_object();
+                }
+                else {
+                    stack.push(Container::Object);
+                    parser.key(visitor)?;
+                    continue 'value;
+                }
+            }
+            b'[' => {
+                parser.bump()?;
+                visitor.start_array();
+                if parser.next_token()? == Some(b']') {
+                    parser.bump()?;
+                    visitor.end_array();
+                }
+                else {
+                    stack.push(Container::Array);
+                    continue 'value;
+                }
+            }
+            b'"' => {
+                parser.bump()?;
+                parser.string()?;
+                visitor.string(&parser.text);
+            }
+            b'-' | b'0'..=b'9' => {
+                parser.number()?;
+                visitor.number(&parser.text);
+            }
+            b't' => {
+                parser.literal(b"true")?;
+                visitor.boolean(true);
+            }
+            b'f' => {
+                parser.literal(b"false")?;
+                visitor.boolean(false);
+            }
+            b'n' => {
+                parser.literal(b"null")?;
+                visitor.null();
+            }
+            _ => {
+                let byte = parser.bump()?;
+                return Err(parser.unexpected(byte));
+            }
+        }
+
+        // A value just ended, close containers until another value is due
+        while let Some(&container) = stack.last() {
+            parser.next_token()?;
+            match (container, parser.bump()?) {
+                (_, b',') => {
+                    if container == Container::Object {
+                        parser.key(visitor)?;
+                    }
+                    continue 'value;
+                }
+                (Container::Object, b'}') => visitor.end_object(),
+                (Container::Array, b']') => visitor.end_array(),
+                (_, byte) => return Err(parser.unexpected(byte)),
+            }
+            stack.pop();
+        }
+
+        return match parser.next_token()? {
+            None => Ok(()),
+            Some(_) => {
+                let byte = parser.bump()?;
+                Err(parser.unexpected(byte))
+            }
+        };
+    }
+}
+
+const LIMB: u32 = 1_000_000_000;
+
+// Unsigned integer in base 10⁹ limbs, least significant first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Magnitude(Vec<u32>);
+
+impl Magnitude {
+    fn parse(digits: &str) -> Self {
+        let digits = digits.as_bytes();
+        let limbs = digits
+            .rchunks(9)
+            .map(|chunk| chunk.iter().fold(0, |limb, &digit| limb * 10 + u32::from(digit - b'0')))
+            .collect();
+        let mut magnitude = Magnitude(limbs);
+        magnitude.trim();
+        magnitude
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+
+    fn add(&mut self, other: &Magnitude) {
+        let mut carry = 0;
+        for i in 0..self.0.len().max(other.0.len()) {
+            if i == self.0.len() {
+                self.0.push(0);
+            }
+            let sum = self.0[i] + other.0.get(i).copied().unwrap_or(0) + carry;
+            self.0[i] = sum % LIMB;
+            carry = sum / LIMB;
+        }
+        if carry > 0 {
+            self.0.push(carry);
+        }
+    }
+
+    // self - other, for other <= self
+    fn sub(&self, other: &Magnitude) -> Magnitude {
+        let mut difference = self.clone();
+        let mut borrow = 0;
+        for (i, limb) in difference.0.iter_mut().enumerate() {
+            let subtrahend = other.0.get(i).copied().unwrap_or(0) + borrow;
+            if *limb >= subtrahend {
+                *limb -= subtrahend;
+                borrow = 0;
+            }
+            else {
+                *limb = *limb + LIMB - subtrahend;
+                borrow = 1;
+            }
+        }
+        difference.trim();
+        difference
+    }
+}
+
+impl Ord for Magnitude {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.len().cmp(&other.0.len()).then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+impl PartialOrd for Magnitude {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Magnitude {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.split_last() {
+            None => write!(f, "0"),
+            Some((most, rest)) => {
+                write!(f, "{}", most)?;
+                rest.iter().rev().try_for_each(|limb| write!(f, "{:09}", limb))
+            }
+        }
+    }
+}
+
+// A sum of JSON numbers. Integers of any length are added exactly; numbers
+// written with a fraction or an exponent are added as floats on the side.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Total {
+    positive: Magnitude,
+    negative: Magnitude,
+    float: f64,
+    has_floats: bool,
+}
+
+impl Total {
+    pub fn add_number(&mut self, text: &str) {
+        if text.contains(['.', 'e', 'E']) {
+            self.float += text.parse::<f64>().unwrap_or(0.0);
+            self.has_floats = true;
+        }
+        else if let Some(digits) = text.strip_prefix('-') {
+            self.negative.add(&Magnitude::parse(digits));
+        }
+        else {
+            self.positive.add(&Magnitude::parse(text));
+        }
+    }
+
+    pub fn add(&mut self, other: &Total) {
+        self.positive.add(&other.positive);
+        self.negative.add(&other.negative);
+        self.float += other.float;
+        self.has_floats |= other.has_floats;
+    }
+
+    // The exact sum of the integers, in decimal
+    pub fn integer(&self) -> String {
+        if self.positive >= self.negative {
+            self.positive.sub(&self.negative).to_string()
+        }
+        else {
+            format!("-{}", self.negative.sub(&self.positive))
+        }
+    }
+
+    pub fn integer_i128(&self) -> Option<i128> {
+        self.integer().parse().ok()
+    }
+
+    // The sum of the numbers that had a fraction or an exponent
+    pub fn float(&self) -> f64 {
+        self.float
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.integer().parse::<f64>().unwrap() + self.float
+    }
+}
+
+impl fmt::Display for Total {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.has_floats {
+            write!(f, "{}", self.to_f64())
+        }
+        else {
+            write!(f, "{}", self.integer())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Exclusion {
+    PropertyValue(String), // objects with a property set to this string
+    Key(String),           // values of properties with this name
+    DeeperThan(usize),     // values inside more than this many containers
+}
+
+#[derive(Debug, Default)]
+struct Frame {
+    total: Total,
+    excluded: bool,
+    skip_next: bool, // set by a key whose value is excluded
+    is_object: bool,
+}
+
+// A visitor summing every number that no exclusion applies to. Each open
+// container keeps its own subtotal, because an object can turn out to be
+// excluded by a property that comes after its numbers.
+#[derive(Debug, Default)]
+pub struct NumberSum {
+    exclusions: Vec<Exclusion>,
+    frames: Vec<Frame>,
+    total: Total,
+}
+
+impl NumberSum {
+    pub fn new(exclusions: Vec<Exclusion>) -> Self {
+        NumberSum {
+            exclusions,
+            ..NumberSum::default()
+        }
+    }
+
+    pub fn total(&self) -> &Total {
+        &self.total
+    }
+
+    // Whether the value starting now is excluded, consuming a pending key
+    fn value_excluded(&mut self) -> bool {
+        let depth = self.frames.len();
+        let too_deep = self
+            .exclusions
+            .iter()
+            .any(|exclusion| matches!(exclusion, Exclusion::DeeperThan(max) if depth > *max));
+        match self.frames.last_mut() {
+            Some(frame) => too_deep || frame.excluded || std::mem::take(&mut frame.skip_next),
+            None => too_deep,
+        }
+    }
+
+    fn open(&mut self, is_object: bool) {
+        let excluded = self.value_excluded();
+        self.frames.push(Frame {
+            excluded,
+            is_object,
+            ..Frame::default()
+        });
+    }
+
+    fn close(&mut self) {
+        let frame = self.frames.pop().unwrap();
+        if frame.excluded {
+            return;
+        }
+        match self.frames.last_mut() {
+            Some(parent) => parent.total.add(&frame.total),
+            None => self.total.add(&frame.total),
+        }
+    }
+}
+
+impl JsonVisitor for NumberSum {
+    fn start_object(&mut self) {
+        self.open(true);
+    }
+
+    fn end_object(&mut self) {
+        self.close();
+    }
+
+    fn start_array(&mut self) {
+        self.open(false);
+    }
+
+    fn end_array(&mut self) {
+        self.close();
+    }
+
+    fn key(&mut self, key: &str) {
+        let skip = self.exclusions.iter().any(|exclusion| matches!(exclusion, Exclusion::Key(name) if name == key));
+        if let Some(frame) = self.frames.last_mut() {
+            frame.skip_next = skip;
+        }
+    }
+
+    fn string(&mut self, value: &str) {
+        self.value_excluded();
+        let matches = self
+            .exclusions
+            .iter()
+            .any(|exclusion| matches!(exclusion, Exclusion::PropertyValue(excluded) if excluded == value));
+        if let Some(frame) = self.frames.last_mut()
+            && frame.is_object
+            && matches
+        {
+            frame.excluded = true;
+        }
+    }
+
+    fn number(&mut self, text: &str) {
+        if self.value_excluded() {
+            return;
+        }
+        match self.frames.last_mut() {
+            Some(frame) => frame.total.add_number(text),
+            None => self.total.add_number(text),
+        }
+    }
+
+    fn boolean(&mut self, _value: bool) {
+        self.value_excluded();
+    }
+
+    fn null(&mut self) {
+        self.value_excluded();
+    }
+}
+
+// Sums the numbers of a document read from `reader`
+pub fn sum_numbers<R: Read>(reader: R, exclusions: Vec<Exclusion>) -> Result<Total, JsonStreamError> {
+    let mut sum = NumberSum::new(exclusions);
+    visit(reader, &mut sum)?;
+    Ok(sum.total)
+}
+
+#[cfg(test)]
+fn sum(document: &str, exclusions: Vec<Exclusion>) -> String {
+    sum_numbers(document.as_bytes(), exclusions).unwrap().to_string()
+}
+
+#[test]
+fn test_puzzle_examples() {
+    let examples = [
+        ("[1,2,3]", "6"),
+        (r#"{"a":2,"b":4}"#, "6"),
+        ("[[[3]]]", "3"),
+        (r#"{"a":{"b":4},"c":-1}"#, "3"),
+        (r#"{"a":[-1,1]}"#, "0"),
+        (r#"[-1,{"a":1}]"#, "0"),
+        ("[]", "0"),
+        ("{}", "0"),
+    ];
+    for (document, expected) in examples {
+        assert_eq!(sum(document, vec![]), expected, "{document}");
+    }
+
+    let red = || vec![Exclusion::PropertyValue("red".to_string())];
+    assert_eq!(sum("[1,2,3]", red()), "6");
+    assert_eq!(sum(r#"[1,{"c":"red","b":2},3]"#, red()), "4");
+    assert_eq!(sum(r#"{"d":"red","e":[1,2,3,4],"f":5}"#, red()), "0");
+    assert_eq!(sum(r#"[1,"red",5]"#, red()), "6");
+    assert_eq!(sum(r#"[1,{"a":[2,{"b":3}],"z":"red"},{"c":[{"r":"red"}],"d":4}]"#, red()), "5");
+}
+
+#[test]
+fn test_exclusions_and_number_forms() {
+    let document = r#" { "keep": [1, {"skip": 10, "x": 2}], "skip": {"y": 100}, "deep": [[[1000]]] } "#;
+    assert_eq!(sum(document, vec![]), "1113");
+    assert_eq!(sum(document, vec![Exclusion::Key("skip".to_string())]), "1003");
+    assert_eq!(sum(document, vec![Exclusion::DeeperThan(2)]), "101");
+    assert_eq!(sum(document, vec![Exclusion::DeeperThan(0)]), "0");
+    assert_eq!(sum("42", vec![Exclusion::DeeperThan(0)]), "42");
+
+    let big = "[123456789012345678901234567890, 9876543210987654321, -1000000000000000000000000000000]";
+    assert_eq!(sum(big, vec![]), "-876543210977777777887777777789");
+    let total = sum_numbers("[1, 0.5, -2.5e1, 3E0, 7]".as_bytes(), vec![]).unwrap();
+    assert_eq!(total.integer_i128(), Some(8));
+    assert_eq!(total.float(), -21.5);
+    assert_eq!(total.to_string(), "-13.5");
+    assert_eq!(sum("[-0, 0, 1000000000, -999999999]", vec![]), "1");
+}
+
+#[test]
+fn test_visitor_strings_and_errors() {
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+    impl JsonVisitor for Recorder {
+        fn key(&mut self, key: &str) {
+            self.0.push(format!("key {key}"));
+        }
+        fn string(&mut self, value: &str) {
+            self.0.push(format!("string {value}"));
+        }
+        fn boolean(&mut self, value: bool) {
+            self.0.push(format!("bool {value}"));
+        }
+        fn null(&mut self) {
+            self.0.push("null".to_string());
+        }
+    }
+
+    let mut recorder = Recorder::default();
+    visit(r#"{"a\"b": ["é😀\n", true, null], "c": false}"#.as_bytes(), &mut recorder).unwrap();
+    assert_eq!(recorder.0, ["key a\"b", "string é😀\n", "bool true", "null", "key c", "bool false"]);
+
+    let error = |document: &str| visit(document.as_bytes(), &mut Recorder::default()).unwrap_err().to_string();
+    assert_eq!(error("[1, 2"), "Unexpected end of input");
+    assert_eq!(error("[1 2]"), "Unexpected '2' at byte 3");
+    assert_eq!(error(r#"{"a" 1}"#), "Unexpected '1' at byte 5");
+    assert_eq!(error("[01]"), "Unexpected '1' at byte 2");
+    assert_eq!(error("[1,]"), "Unexpected ']' at byte 3");
+    assert_eq!(error("{} {}"), "Unexpected '{' at byte 3");
+    assert_eq!(error(r#"["\ud83d"]"#), "Unexpected '\"' at byte 8");
+    assert_eq!(error(r#"["\x"]"#), "Unexpected 'x' at byte 3");
+    assert_eq!(error("[tru]"), "Unexpected ']' at byte 4");
+}
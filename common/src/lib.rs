@@ -1 +1,17 @@
+pub mod automaton;
+pub mod circuit;
+pub mod combat;
+pub mod dlx;
 pub mod error;
+pub mod grammar;
+pub mod interval;
+pub mod json_stream;
+pub mod look_and_say;
+pub mod mining;
+pub mod password;
+pub mod polygon;
+pub mod query;
+pub mod race;
+pub mod recipe;
+pub mod subset_sum;
+pub mod vm;
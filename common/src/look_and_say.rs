@@ -0,0 +1,315 @@
+use std::collections::{HashMap, HashSet};
+use std::iter::Peekable;
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum LookAndSayError {
+    #[error("A look-and-say seed must be a non-empty string of digits, got {0:?}")]
+    InvalidSeed(String),
+}
+
+// One look-and-say step over a stream of ASCII digits, yielding ASCII digits
+pub struct Step<I: Iterator<Item = u8>> {
+    digits: Peekable<I>,
+    pending: Vec<u8>, // reversed digits of the current "count, digit" pair
+}
+
+impl<I: Iterator<Item = u8>> Step<I> {
+    pub fn new(digits: I) -> Self {
+        Step {
+            digits: digits.peekable(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Step<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if let Some(digit) = self.pending.pop() {
+            return Some(digit);
+        }
+
+        let digit = self.digits.next()?;
+        let mut count = 1;
+        while self.digits.next_if_eq(&digit).is_some() {
+            count += 1;
+        }
+
+        self.pending.push(digit);
+        self.pending.extend(count.to_string().bytes().rev());
+        self.pending.pop()
+    }
+}
+
+// The digits of `seed` after `steps` iterations, produced lazily through a
+// chain of `steps` iterators. Memory stays proportional to `steps` but the
+// time is still the length of the result, so this is meant for small counts.
+pub fn stream(seed: &str, steps: usize) -> Box<dyn Iterator<Item = u8> + '_> {
+    let mut digits: Box<dyn Iterator<Item = u8> + '_> = Box::new(seed.bytes());
+    for _ in 0..steps {
+        digits = Box::new(Step::new(digits));
+    }
+
+    digits
+}
+
+pub fn look_and_say(sequence: &str) -> String {
+    String::from_utf8(Step::new(sequence.bytes()).collect()).unwrap()
+}
+
+// Whether `left` + `right` evolve as two independent strings forever. The
+// last digit of a string never changes under look-and-say, so the two halves
+// stay apart exactly as long as the first digit of the evolving right half
+// differs from the last digit of the left one. The first digit only depends
+// on a prefix of the right half, so the check follows a bounded prefix until
+// its state repeats. Anything undecided is treated as joined, which is always
+// safe.
+fn splits(last: u8, right: &[u8]) -> bool {
+    const PREFIX: usize = 64;
+
+    let mut complete = right.len() <= PREFIX;
+    let mut prefix = right[..right.len().min(PREFIX)].to_vec();
+    let mut seen = HashSet::new();
+    while seen.insert((prefix.clone(), complete)) {
+        if prefix.first().is_none_or(|&first| first == last) {
+            return false;
+        }
+
+        // With only a prefix of the real string its last run may be cut
+        // short, so the pair produced for it is unreliable
+        let mut next = Vec::with_capacity(prefix.len() * 2);
+        let mut runs = prefix.chunk_by(|a, b| a == b).peekable();
+        while let Some(run) = runs.next() {
+            if runs.peek().is_none() && !complete {
+                break;
+            }
+            next.extend(run.len().to_string().bytes());
+            next.push(run[0]);
+        }
+
+        if next.len() > PREFIX {
+            next.truncate(PREFIX);
+            complete = false;
+        }
+        prefix = next;
+    }
+
+    true
+}
+
+// Splits a digit string into the pieces that evolve independently forever.
+// For strings that have aged a few steps these are Conway's elements.
+pub fn decompose(sequence: &str) -> Vec<&str> {
+    let bytes = sequence.as_bytes();
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for i in 1..bytes.len() {
+        if bytes[i - 1] != bytes[i] && splits(bytes[i - 1], &bytes[i..]) {
+            pieces.push(&sequence[start..i]);
+            start = i;
+        }
+    }
+    if start < sequence.len() {
+        pieces.push(&sequence[start..]);
+    }
+
+    pieces
+}
+
+// Every element reachable from a seed and what each one decays into after a
+// step. Counting elements instead of building strings turns N steps into a
+// matrix power over the elements: O(k³ log N) for k elements, which is 92
+// plus a few short-lived pieces of the seed for any puzzle input.
+#[derive(Debug, Clone)]
+pub struct ElementTable {
+    elements: Vec<String>,
+    index: HashMap<String, usize>,
+    decays: Vec<Vec<usize>>,
+    seed: Vec<usize>,
+}
+
+impl ElementTable {
+    pub fn new(seed: &str) -> Result<Self, LookAndSayError> {
+        if seed.is_empty() || !seed.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(LookAndSayError::InvalidSeed(seed.to_string()));
+        }
+
+        let mut table = ElementTable {
+            elements: Vec::new(),
+            index: HashMap::new(),
+            decays: Vec::new(),
+            seed: Vec::new(),
+        };
+        table.seed = decompose(seed).into_iter().map(|piece| table.intern(piece)).collect();
+
+        let mut next = 0;
+        while next < table.elements.len() {
+            let evolved = look_and_say(&table.elements[next]);
+            let decay = decompose(&evolved).into_iter().map(|piece| table.intern(piece)).collect();
+            table.decays.push(decay);
+            next += 1;
+        }
+
+        Ok(table)
+    }
+
+    fn intern(&mut self, piece: &str) -> usize {
+        if let Some(&id) = self.index.get(piece) {
+            return id;
+        }
+
+        self.elements.push(piece.to_string());
+        self.index.insert(piece.to_string(), self.elements.len() - 1);
+        self.elements.len() - 1
+    }
+
+    pub fn elements(&self) -> &[String] {
+        &self.elements
+    }
+
+    pub fn element(&self, piece: &str) -> Option<usize> {
+        self.index.get(piece).copied()
+    }
+
+    // The elements `element` turns into after one step, in order
+    pub fn decay(&self, element: usize) -> &[usize] {
+        &self.decays[element]
+    }
+
+    // Elements that keep reappearing forever, i.e. the ones on a decay cycle
+    pub fn recurrent(&self) -> Vec<usize> {
+        (0..self.elements.len())
+            .filter(|&element| {
+                let mut seen = HashSet::new();
+                let mut stack = self.decays[element].clone();
+                while let Some(current) = stack.pop() {
+                    if current == element {
+                        return true;
+                    }
+                    if seen.insert(current) {
+                        stack.extend(&self.decays[current]);
+                    }
+                }
+                false
+            })
+            .collect()
+    }
+
+    fn multiply(a: &[Vec<u128>], b: &[Vec<u128>]) -> Option<Vec<Vec<u128>>> {
+        let n = a.len();
+        let mut product = vec![vec![0u128; n]; n];
+        for (row, a_row) in product.iter_mut().zip(a) {
+            for (&factor, b_row) in a_row.iter().zip(b) {
+                if factor == 0 {
+                    continue;
+                }
+                for (cell, &value) in row.iter_mut().zip(b_row) {
+                    *cell = cell.checked_add(factor.checked_mul(value)?)?;
+                }
+            }
+        }
+
+        Some(product)
+    }
+
+    // How many of each element the seed holds after `steps` iterations, or
+    // None if a count overflows
+    pub fn counts_after(&self, steps: u64) -> Option<Vec<u128>> {
+        let n = self.elements.len();
+        let mut transition = vec![vec![0u128; n]; n];
+        for (element, decay) in self.decays.iter().enumerate() {
+            for &product in decay {
+                transition[element][product] += 1;
+            }
+        }
+
+        let mut power = (0..n)
+            .map(|i| (0..n).map(|j| u128::from(i == j)).collect())
+            .collect::<Vec<Vec<u128>>>();
+        let mut remaining = steps;
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                power = Self::multiply(&power, &transition)?;
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                transition = Self::multiply(&transition, &transition)?;
+            }
+        }
+
+        let mut counts = vec![0u128; n];
+        for &element in &self.seed {
+            for (count, &value) in counts.iter_mut().zip(&power[element]) {
+                *count = count.checked_add(value)?;
+            }
+        }
+
+        Some(counts)
+    }
+
+    // Length of the seed after `steps` iterations, or None on overflow
+    pub fn length_after(&self, steps: u64) -> Option<u128> {
+        self.counts_after(steps)?
+            .iter()
+            .zip(&self.elements)
+            .try_fold(0u128, |total, (&count, element)| total.checked_add(count.checked_mul(element.len() as u128)?))
+    }
+}
+
+#[test]
+fn test_look_and_say_steps() {
+    assert_eq!(look_and_say("1"), "11");
+    assert_eq!(look_and_say("111221"), "312211");
+    assert_eq!(look_and_say("1111111111"), "101");
+    assert_eq!(String::from_utf8(stream("1", 5).collect()).unwrap(), "312211");
+    assert_eq!(stream("1113122113", 0).count(), 10);
+}
+
+#[test]
+fn test_element_table_matches_direct_iteration() {
+    for seed in ["1", "3", "1113122113", "3113322113", "22", "1321131112"] {
+        let table = ElementTable::new(seed).unwrap();
+        let mut sequence = seed.to_string();
+        for steps in 0..=25 {
+            assert_eq!(table.length_after(steps), Some(sequence.len() as u128), "{seed} after {steps}");
+            sequence = look_and_say(&sequence);
+        }
+        assert_eq!(table.length_after(30), Some(stream(seed, 30).count() as u128));
+    }
+}
+
+#[test]
+fn test_conway_elements() {
+    // Hydrogen is the fixed point, the recurrent elements are Conway's 92
+    let table = ElementTable::new("22").unwrap();
+    assert_eq!(table.elements(), &["22".to_string()]);
+    assert_eq!(table.decay(0), &[0]);
+
+    let table = ElementTable::new("1").unwrap();
+    assert_eq!(table.recurrent().len(), 92);
+    assert!(table.element("13112221133211322112211213322112").is_some());
+
+    // Pieces evolved on their own concatenate to the evolved whole
+    let mut sequence = "1".to_string();
+    for _ in 0..20 {
+        let pieces = decompose(&sequence);
+        assert_eq!(pieces.concat(), sequence);
+        let mut evolved = pieces.iter().map(|piece| piece.to_string()).collect::<Vec<String>>();
+        let mut whole = sequence.clone();
+        for _ in 0..8 {
+            evolved = evolved.iter().map(|piece| look_and_say(piece)).collect();
+            whole = look_and_say(&whole);
+            assert_eq!(evolved.concat(), whole);
+        }
+        sequence = look_and_say(&sequence);
+    }
+
+    // Conway's constant shows up as the growth rate
+    let ratio = table.length_after(300).unwrap() as f64 / table.length_after(299).unwrap() as f64;
+    assert!((ratio - 1.303577269).abs() < 1e-6);
+    assert_eq!(ElementTable::new("12a").unwrap_err(), LookAndSayError::InvalidSeed("12a".to_string()));
+    assert!(ElementTable::new("").is_err());
+}
@@ -0,0 +1,160 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+// Nonces handed to a worker at a time. Large enough that the shared counter
+// is rarely touched, small enough that little work is wasted past the answer.
+const CHUNK: u64 = 4096;
+
+// An MD5 digest has 32 hex digits, so no nonce gets past this many zeros
+pub const MAX_ZERO_NIBBLES: usize = 32;
+
+// Number of leading zero hex digits in an MD5 digest
+pub fn leading_zero_nibbles(digest: &md5::Digest) -> usize {
+    (u128::from_be_bytes(digest.0).leading_zeros() / 4) as usize
+}
+
+// Writes the decimal digits of `value` to the end of `buffer` and returns them
+fn decimal(value: u64, buffer: &mut [u8; 20]) -> &[u8] {
+    let mut start = buffer.len();
+    let mut value = value;
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+
+    &buffer[start..]
+}
+
+// Searches for the lowest nonces whose MD5 of secret + nonce starts with
+// enough zero nibbles. The secret is fed to an MD5 context once and every
+// nonce continues from a copy of it, so no strings are built per hash.
+#[derive(Clone)]
+pub struct Miner {
+    prefix: md5::Context,
+    threads: usize,
+}
+
+impl Miner {
+    pub fn new(secret: &str) -> Self {
+        let mut prefix = md5::Context::new();
+        prefix.consume(secret.as_bytes());
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+
+        Miner { prefix, threads }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn hash(&self, nonce: u64) -> md5::Digest {
+        let mut context = self.prefix.clone();
+        context.consume(decimal(nonce, &mut [0; 20]));
+        context.finalize()
+    }
+
+    // The lowest nonce below `limit` for each zero nibble count in
+    // `thresholds`, all answered by a single pass. Workers claim chunks in
+    // increasing order and keep going until every threshold has an answer
+    // below the next unclaimed chunk, so the results are the same as a
+    // sequential scan no matter how the chunks end up scheduled. Thresholds
+    // above MAX_ZERO_NIBBLES are None without hashing anything for them, and
+    // since `limit` itself is never tried, u64::MAX is free to mean "none yet".
+    pub fn lowest_nonces(&self, thresholds: &[usize], limit: u64) -> Vec<Option<u64>> {
+        let best = thresholds.iter().map(|_| AtomicU64::new(u64::MAX)).collect::<Vec<AtomicU64>>();
+        let next_chunk = AtomicU64::new(0);
+        let answered = |start: u64| {
+            thresholds
+                .iter()
+                .zip(&best)
+                .all(|(&threshold, nonce)| threshold > MAX_ZERO_NIBBLES || nonce.load(Ordering::Relaxed) <= start)
+        };
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads {
+                scope.spawn(|| {
+                    loop {
+                        // Stops handing out chunks at `limit` rather than
+                        // wrapping around to 0 near u64::MAX
+                        let claim = next_chunk.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |start| {
+                            (start < limit).then(|| start.saturating_add(CHUNK))
+                        });
+                        let Ok(start) = claim else {
+                            break;
+                        };
+                        if answered(start) {
+                            break;
+                        }
+
+                        for nonce in start..start.saturating_add(CHUNK).min(limit) {
+                            let zeros = leading_zero_nibbles(&self.hash(nonce));
+                            for (&threshold, found) in thresholds.iter().zip(&best) {
+                                if zeros >= threshold {
+                                    found.fetch_min(nonce, Ordering::Relaxed);
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        best.into_iter()
+            .map(|nonce| Some(nonce.into_inner()).filter(|&nonce| nonce != u64::MAX))
+            .collect()
+    }
+
+    // Shorthand for a single threshold without a limit
+    pub fn lowest_nonce(&self, zeros: usize) -> Option<u64> {
+        self.lowest_nonces(&[zeros], u64::MAX)[0]
+    }
+}
+
+#[test]
+fn test_miner_hashes_like_md5() {
+    let miner = Miner::new("abcdef");
+    for nonce in [0, 7, 10, 609043, u64::MAX] {
+        assert_eq!(miner.hash(nonce), md5::compute(format!("abcdef{}", nonce)));
+    }
+    assert_eq!(leading_zero_nibbles(&miner.hash(609043)), 5);
+    assert_eq!(leading_zero_nibbles(&md5::Digest([0; 16])), 32);
+    assert_eq!(leading_zero_nibbles(&md5::Digest([0x0f; 16])), 1);
+}
+
+#[test]
+fn test_lowest_nonces_match_sequential_scan() {
+    let miner = Miner::new("pqrstuv");
+    let thresholds = [0, 1, 2, 3, 33];
+    let limit = 50_000;
+    let expected = thresholds
+        .iter()
+        .map(|&zeros| (0..limit).find(|&nonce| leading_zero_nibbles(&miner.hash(nonce)) >= zeros))
+        .collect::<Vec<Option<u64>>>();
+
+    for threads in [1, 3, 8] {
+        let miner = miner.clone().with_threads(threads);
+        assert_eq!(miner.lowest_nonces(&thresholds, limit), expected, "{threads} threads");
+    }
+    assert_eq!(expected[4], None);
+    assert_eq!(miner.lowest_nonces(&[], limit), vec![]);
+}
+
+#[test]
+fn test_puzzle_examples() {
+    assert_eq!(Miner::new("abcdef").lowest_nonce(5), Some(609043));
+    assert_eq!(Miner::new("pqrstuv").with_threads(4).lowest_nonces(&[4, 5], u64::MAX)[1], Some(1048970));
+}
+
+#[test]
+fn test_unreachable_thresholds() {
+    // More zeros than a digest has digits end the search at once
+    let miner = Miner::new("abcdef");
+    assert_eq!(miner.lowest_nonce(MAX_ZERO_NIBBLES + 1), None);
+    assert_eq!(miner.lowest_nonces(&[0, 40], u64::MAX), vec![Some(0), None]);
+    assert_eq!(miner.lowest_nonces(&[0], 0), vec![None]);
+}
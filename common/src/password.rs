@@ -0,0 +1,259 @@
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PasswordError {
+    #[error("The alphabet is empty")]
+    EmptyAlphabet,
+    #[error("Letter '{0}' appears twice in the alphabet")]
+    DuplicateLetter(char),
+    #[error("Letter '{0}' is not in the alphabet")]
+    UnknownLetter(char),
+    #[error("Every letter of the alphabet is forbidden")]
+    NothingAllowed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    Straight(usize),      // a run of this many letters that follow each other in the alphabet
+    Forbidden(Vec<char>), // none of these letters anywhere
+    Pairs(usize),         // this many different letters appearing doubled, like "aa" and "zz"
+}
+
+// A set of rules over an ordered alphabet. Passwords are counted upward like
+// numbers written in that alphabet with a fixed number of digits.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    alphabet: Vec<char>,
+    allowed: Vec<bool>,
+    rules: Vec<Rule>,
+}
+
+impl Policy {
+    pub fn new(alphabet: &str) -> Result<Self, PasswordError> {
+        let alphabet = alphabet.chars().collect::<Vec<char>>();
+        if alphabet.is_empty() {
+            return Err(PasswordError::EmptyAlphabet);
+        }
+        let mut seen = HashSet::new();
+        if let Some(&letter) = alphabet.iter().find(|&&letter| !seen.insert(letter)) {
+            return Err(PasswordError::DuplicateLetter(letter));
+        }
+
+        Ok(Policy {
+            allowed: vec![true; alphabet.len()],
+            alphabet,
+            rules: Vec::new(),
+        })
+    }
+
+    // Santa's rules: lowercase letters, a straight of three, no i, o or l and
+    // two different pairs
+    pub fn santa() -> Self {
+        let mut policy = Policy::new("abcdefghijklmnopqrstuvwxyz").unwrap();
+        policy.add_rule(Rule::Straight(3)).unwrap();
+        policy.add_rule(Rule::Forbidden(vec!['i', 'o', 'l'])).unwrap();
+        policy.add_rule(Rule::Pairs(2)).unwrap();
+        policy
+    }
+
+    pub fn add_rule(&mut self, rule: Rule) -> Result<(), PasswordError> {
+        if let Rule::Forbidden(letters) = &rule {
+            for &letter in letters {
+                let index = self.index(letter)?;
+                self.allowed[index] = false;
+            }
+        }
+
+        self.rules.push(rule);
+        Ok(())
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    fn index(&self, letter: char) -> Result<usize, PasswordError> {
+        self.alphabet
+            .iter()
+            .position(|&c| c == letter)
+            .ok_or(PasswordError::UnknownLetter(letter))
+    }
+
+    fn encode(&self, password: &str) -> Result<Vec<usize>, PasswordError> {
+        password.chars().map(|letter| self.index(letter)).collect()
+    }
+
+    fn decode(&self, digits: &[usize]) -> String {
+        digits.iter().map(|&digit| self.alphabet[digit]).collect()
+    }
+
+    fn satisfies(&self, digits: &[usize], rule: &Rule) -> bool {
+        match rule {
+            Rule::Straight(0) => true,
+            Rule::Straight(length) => digits
+                .windows(*length)
+                .any(|window| window.windows(2).all(|pair| pair[1] == pair[0] + 1)),
+            Rule::Forbidden(_) => digits.iter().all(|&digit| self.allowed[digit]),
+            Rule::Pairs(count) => {
+                let letters = digits
+                    .windows(2)
+                    .filter(|pair| pair[0] == pair[1])
+                    .map(|pair| pair[0])
+                    .collect::<HashSet<usize>>();
+                letters.len() >= *count
+            }
+        }
+    }
+
+    pub fn is_valid(&self, password: &str) -> bool {
+        self.encode(password)
+            .is_ok_and(|digits| self.rules.iter().all(|rule| self.satisfies(&digits, rule)))
+    }
+
+    // Moves digit `position` to the next allowed letter, carrying leftward,
+    // and sets everything after it to the lowest allowed letter. False once
+    // the counter runs past the last password of this length.
+    fn advance(&self, digits: &mut [usize], position: usize) -> bool {
+        let lowest = self.allowed.iter().position(|&allowed| allowed).unwrap();
+        for digit in &mut digits[position + 1..] {
+            *digit = lowest;
+        }
+
+        for digit in digits[..=position].iter_mut().rev() {
+            match (*digit + 1..self.alphabet.len()).find(|&next| self.allowed[next]) {
+                Some(next) => {
+                    *digit = next;
+                    return true;
+                }
+                None => *digit = lowest,
+            }
+        }
+
+        false
+    }
+
+    // The passwords after `current` that satisfy every rule, in order. A
+    // forbidden letter rules out every password sharing the prefix up to it,
+    // so the search jumps past all of them at once instead of counting
+    // through them.
+    pub fn passwords_after(&self, current: &str) -> Result<Passwords<'_>, PasswordError> {
+        if !self.allowed.contains(&true) {
+            return Err(PasswordError::NothingAllowed);
+        }
+
+        let mut digits = self.encode(current)?;
+        let last = digits.len().checked_sub(1);
+        let exhausted = last.is_none_or(|last| !self.advance(&mut digits, last));
+        Ok(Passwords {
+            policy: self,
+            digits,
+            exhausted,
+        })
+    }
+
+    pub fn next_password(&self, current: &str) -> Result<Option<String>, PasswordError> {
+        Ok(self.passwords_after(current)?.next())
+    }
+}
+
+pub struct Passwords<'a> {
+    policy: &'a Policy,
+    digits: Vec<usize>, // the next candidate to look at
+    exhausted: bool,
+}
+
+impl Iterator for Passwords<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let policy = self.policy;
+        while !self.exhausted {
+            if let Some(position) = self.digits.iter().position(|&digit| !policy.allowed[digit]) {
+                self.exhausted = !policy.advance(&mut self.digits, position);
+                continue;
+            }
+
+            let candidate = policy.rules.iter().all(|rule| policy.satisfies(&self.digits, rule));
+            let password = policy.decode(&self.digits);
+            let last = self.digits.len() - 1;
+            self.exhausted = !policy.advance(&mut self.digits, last);
+            if candidate {
+                return Some(password);
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+fn brute_force(policy: &Policy, current: &str) -> Vec<String> {
+    let letters = policy.alphabet.len();
+    let mut digits = policy.encode(current).unwrap();
+    let mut valid = Vec::new();
+    loop {
+        let Some(position) = digits.iter().rposition(|&digit| digit + 1 < letters) else {
+            return valid;
+        };
+        digits[position] += 1;
+        for digit in &mut digits[position + 1..] {
+            *digit = 0;
+        }
+        if policy.is_valid(&policy.decode(&digits)) {
+            valid.push(policy.decode(&digits));
+        }
+    }
+}
+
+#[test]
+fn test_santa_policy() {
+    let policy = Policy::santa();
+    assert!(!policy.is_valid("hijklmmn"));
+    assert!(!policy.is_valid("abbceffg"));
+    assert!(!policy.is_valid("abbcegjk"));
+    assert!(policy.is_valid("abcdffaa"));
+    assert!(!policy.is_valid("abcdFfaa"));
+
+    assert_eq!(policy.next_password("abcdefgh"), Ok(Some("abcdffaa".to_string())));
+    assert_eq!(policy.next_password("ghijklmn"), Ok(Some("ghjaabcc".to_string())));
+    assert_eq!(policy.next_password("zzzzzzzz"), Ok(None));
+    let next = policy.passwords_after("ghjaabcc").unwrap().take(2).collect::<Vec<String>>();
+    assert!(next.iter().all(|password| policy.is_valid(password) && password.as_str() > "ghjaabcc"));
+    assert!(next[0] < next[1]);
+}
+
+#[test]
+fn test_passwords_match_brute_force() {
+    let rule_sets = [
+        vec![Rule::Straight(2), Rule::Forbidden(vec!['b'])],
+        vec![Rule::Pairs(2), Rule::Forbidden(vec!['a', 'e'])],
+        vec![Rule::Straight(3), Rule::Pairs(1), Rule::Forbidden(vec!['d'])],
+        vec![Rule::Forbidden(vec!['c', 'b', 'e'])],
+        vec![Rule::Straight(1)],
+    ];
+    for rules in rule_sets {
+        let mut policy = Policy::new("abcde").unwrap();
+        for rule in &rules {
+            policy.add_rule(rule.clone()).unwrap();
+        }
+        for current in ["aaaaa", "abdce", "ceeee", "eeeee", "dbaca"] {
+            assert_eq!(
+                policy.passwords_after(current).unwrap().collect::<Vec<String>>(),
+                brute_force(&policy, current),
+                "{rules:?} after {current}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_policy_errors() {
+    assert_eq!(Policy::new("").unwrap_err(), PasswordError::EmptyAlphabet);
+    assert_eq!(Policy::new("abca").unwrap_err(), PasswordError::DuplicateLetter('a'));
+    let mut policy = Policy::new("xy").unwrap();
+    assert_eq!(policy.add_rule(Rule::Forbidden(vec!['z'])), Err(PasswordError::UnknownLetter('z')));
+    policy.add_rule(Rule::Forbidden(vec!['x', 'y'])).unwrap();
+    assert!(matches!(policy.passwords_after("xy"), Err(PasswordError::NothingAllowed)));
+}
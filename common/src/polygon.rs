@@ -0,0 +1,254 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PolygonError {
+    #[error("An orthogonal polygon needs at least four vertices, got {0}")]
+    TooFewVertices(usize),
+    #[error("Edge from {0:?} to {1:?} is neither horizontal nor vertical")]
+    DiagonalEdge((i64, i64), (i64, i64)),
+}
+
+// One compressed axis: every distinct vertex coordinate gets its own slot and
+// every non-empty run of coordinates between two of them shares one more slot.
+// Cells of the compressed grid are then either entirely inside or entirely
+// outside the polygon.
+#[derive(Debug, Clone)]
+struct Axis {
+    starts: Vec<i64>,
+    lengths: Vec<i64>,
+}
+
+impl Axis {
+    fn new(mut coords: Vec<i64>) -> Self {
+        coords.sort_unstable();
+        coords.dedup();
+        let mut starts = Vec::with_capacity(coords.len() * 2);
+        let mut lengths = Vec::with_capacity(coords.len() * 2);
+        for (i, &coord) in coords.iter().enumerate() {
+            starts.push(coord);
+            lengths.push(1);
+            if let Some(&next) = coords.get(i + 1)
+                && next - coord > 1
+            {
+                starts.push(coord + 1);
+                lengths.push(next - coord - 1);
+            }
+        }
+
+        Axis { starts, lengths }
+    }
+
+    fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    fn index(&self, coord: i64) -> Option<usize> {
+        let i = self.starts.partition_point(|&start| start <= coord).checked_sub(1)?;
+        if coord < self.starts[i] + self.lengths[i] {
+            Some(i)
+        }
+        else {
+            None
+        }
+    }
+
+    // Slot of a coordinate that is known to be a vertex coordinate
+    fn vertex_index(&self, coord: i64) -> usize {
+        self.starts.binary_search(&coord).unwrap()
+    }
+}
+
+// A simple polygon with axis-aligned edges on the integer grid, e.g. a loop of
+// tile positions. Tiles on the boundary count as inside.
+#[derive(Debug, Clone)]
+pub struct OrthogonalPolygon {
+    xs: Axis,
+    ys: Axis,
+    inside: Vec<Vec<bool>>,
+    // outside_prefix[r][c] counts the outside compressed cells above and left of (r, c)
+    outside_prefix: Vec<Vec<u32>>,
+    area: i64,
+}
+
+impl OrthogonalPolygon {
+    pub fn new(vertices: &[(i64, i64)]) -> Result<Self, PolygonError> {
+        if vertices.len() < 4 {
+            return Err(PolygonError::TooFewVertices(vertices.len()));
+        }
+
+        let edges = (0..vertices.len())
+            .map(|i| (vertices[i], vertices[(i + 1) % vertices.len()]))
+            .collect::<Vec<_>>();
+        if let Some(&(from, to)) = edges.iter().find(|&&((x1, y1), (x2, y2))| x1 != x2 && y1 != y2) {
+            return Err(PolygonError::DiagonalEdge(from, to));
+        }
+
+        let xs = Axis::new(vertices.iter().map(|&(x, _)| x).collect());
+        let ys = Axis::new(vertices.iter().map(|&(_, y)| y).collect());
+        let mut inside = vec![vec![false; xs.len()]; ys.len()];
+
+        for &((x1, y1), (x2, y2)) in &edges {
+            let (c1, c2) = (xs.vertex_index(x1), xs.vertex_index(x2));
+            let (r1, r2) = (ys.vertex_index(y1), ys.vertex_index(y2));
+            for row in inside.iter_mut().take(r1.max(r2) + 1).skip(r1.min(r2)) {
+                for cell in row.iter_mut().take(c1.max(c2) + 1).skip(c1.min(c2)) {
+                    *cell = true;
+                }
+            }
+        }
+
+        // Even-odd ray cast to the right from the first tile of each cell,
+        // counting vertical edges half-open so vertices are crossed once
+        for (r, row) in inside.iter_mut().enumerate() {
+            let y = ys.starts[r];
+            let mut crossings = edges
+                .iter()
+                .filter(|&&((x1, y1), (x2, y2))| x1 == x2 && y1.min(y2) <= y && y < y1.max(y2))
+                .map(|&((x, _), _)| x)
+                .collect::<Vec<i64>>();
+            crossings.sort_unstable();
+
+            let mut remaining = crossings.len();
+            for (c, cell) in row.iter_mut().enumerate() {
+                let x = xs.starts[c];
+                while remaining > 0 && crossings[crossings.len() - remaining] <= x {
+                    remaining -= 1;
+                }
+                if remaining % 2 == 1 {
+                    *cell = true;
+                }
+            }
+        }
+
+        let mut outside_prefix = vec![vec![0; xs.len() + 1]; ys.len() + 1];
+        let mut area = 0;
+        for r in 0..ys.len() {
+            for c in 0..xs.len() {
+                let outside = if inside[r][c] {
+                    area += xs.lengths[c] * ys.lengths[r];
+                    0
+                }
+                else {
+                    1
+                };
+                outside_prefix[r + 1][c + 1] =
+                    outside_prefix[r][c + 1] + outside_prefix[r + 1][c] - outside_prefix[r][c] + outside;
+            }
+        }
+
+        Ok(OrthogonalPolygon {
+            xs,
+            ys,
+            inside,
+            outside_prefix,
+            area,
+        })
+    }
+
+    // Number of tiles inside the polygon, boundary included
+    pub fn area(&self) -> i64 {
+        self.area
+    }
+
+    // (columns, rows) of the compressed grid
+    pub fn compressed_size(&self) -> (usize, usize) {
+        (self.xs.len(), self.ys.len())
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        match (self.xs.index(x), self.ys.index(y)) {
+            (Some(c), Some(r)) => self.inside[r][c],
+            _ => false,
+        }
+    }
+
+    // Whether every tile of the rectangle spanned by the two corners (both
+    // inclusive) lies inside the polygon. Two binary searches per axis and an
+    // O(1) prefix sum lookup.
+    pub fn contains_rect(&self, (x1, y1): (i64, i64), (x2, y2): (i64, i64)) -> bool {
+        let (Some(c1), Some(c2)) = (self.xs.index(x1.min(x2)), self.xs.index(x1.max(x2))) else {
+            return false;
+        };
+        let (Some(r1), Some(r2)) = (self.ys.index(y1.min(y2)), self.ys.index(y1.max(y2))) else {
+            return false;
+        };
+
+        let prefix = &self.outside_prefix;
+        let outside = prefix[r2 + 1][c2 + 1] + prefix[r1][c1] - prefix[r1][c2 + 1] - prefix[r2 + 1][c1];
+        outside == 0
+    }
+}
+
+#[cfg(test)]
+fn rasterize(vertices: &[(i64, i64)], width: i64, height: i64) -> Vec<Vec<bool>> {
+    // Tile-by-tile reference: boundary tiles plus tiles whose centre is enclosed
+    let mut grid = vec![vec![false; width as usize]; height as usize];
+    for y in 0..height {
+        for x in 0..width {
+            let mut on_edge = false;
+            let mut crossings = 0;
+            for i in 0..vertices.len() {
+                let (x1, y1) = vertices[i];
+                let (x2, y2) = vertices[(i + 1) % vertices.len()];
+                if x1.min(x2) <= x && x <= x1.max(x2) && y1.min(y2) <= y && y <= y1.max(y2) {
+                    on_edge = true;
+                }
+                if x1 == x2 && x1 > x && y1.min(y2) <= y && y < y1.max(y2) {
+                    crossings += 1;
+                }
+            }
+            grid[y as usize][x as usize] = on_edge || crossings % 2 == 1;
+        }
+    }
+
+    grid
+}
+
+#[test]
+fn test_polygon_matches_rasterized_grid() {
+    let shapes: [&[(i64, i64)]; 3] = [
+        &[(7, 1), (11, 1), (11, 7), (9, 7), (9, 5), (2, 5), (2, 3), (7, 3)],
+        &[(1, 1), (8, 1), (8, 8), (6, 8), (6, 3), (3, 3), (3, 8), (1, 8)],
+        &[(0, 0), (4, 0), (4, 2), (6, 2), (6, 0), (9, 0), (9, 6), (0, 6)],
+    ];
+    for vertices in shapes {
+        let polygon = OrthogonalPolygon::new(vertices).unwrap();
+        let grid = rasterize(vertices, 13, 10);
+        let area = grid.iter().flatten().filter(|&&tile| tile).count() as i64;
+        assert_eq!(polygon.area(), area);
+
+        for y1 in 0..10 {
+            for x1 in 0..13 {
+                assert_eq!(polygon.contains(x1, y1), grid[y1 as usize][x1 as usize]);
+                for y2 in y1..10 {
+                    for x2 in x1..13 {
+                        let expected = (y1..=y2).all(|y| (x1..=x2).all(|x| grid[y as usize][x as usize]));
+                        assert_eq!(polygon.contains_rect((x2, y1), (x1, y2)), expected);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_polygon_rejects_diagonal_edges() {
+    assert_eq!(
+        OrthogonalPolygon::new(&[(0, 0), (4, 0), (4, 4), (1, 3)]).unwrap_err(),
+        PolygonError::DiagonalEdge((4, 4), (1, 3))
+    );
+    assert_eq!(
+        OrthogonalPolygon::new(&[(0, 0), (4, 0), (4, 4)]).unwrap_err(),
+        PolygonError::TooFewVertices(3)
+    );
+}
+
+#[test]
+fn test_polygon_large_coordinates() {
+    let polygon = OrthogonalPolygon::new(&[(0, 0), (100_000, 0), (100_000, 50_000), (0, 50_000)]).unwrap();
+    assert_eq!(polygon.compressed_size(), (3, 3));
+    assert_eq!(polygon.area(), 100_001 * 50_001);
+    assert!(polygon.contains_rect((0, 0), (100_000, 50_000)));
+    assert!(!polygon.contains_rect((0, 0), (100_001, 50_000)));
+    assert!(!polygon.contains(-1, 0));
+}
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QueryError {
+    #[error("{message} at position {position}")]
+    Parse { position: usize, message: String },
+    #[error("Invalid record: {0}")]
+    Record(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Operator {
+    fn symbol(self) -> &'static str {
+        match self {
+            Operator::Equal => "=",
+            Operator::NotEqual => "!=",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+        }
+    }
+
+    pub fn holds(self, left: i64, right: i64) -> bool {
+        match self {
+            Operator::Equal => left == right,
+            Operator::NotEqual => left != right,
+            Operator::Less => left < right,
+            Operator::LessOrEqual => left <= right,
+            Operator::Greater => left > right,
+            Operator::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    pub property: String,
+    pub operator: Operator,
+    pub value: i64,
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.property, self.operator.symbol(), self.value)
+    }
+}
+
+// A named set of integer properties, any of which may be unknown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub name: String,
+    pub properties: HashMap<String, i64>,
+}
+
+impl Record {
+    // "Sue 12: cars: 9, akitas: 3, goldfish: 0"
+    pub fn parse(line: &str) -> Result<Self, QueryError> {
+        let (name, rest) = line.split_once(':').ok_or_else(|| QueryError::Record(line.to_string()))?;
+        let mut properties = HashMap::new();
+        for property in rest.split(',').filter(|property| !property.trim().is_empty()) {
+            let (property, value) = property
+                .split_once(':')
+                .and_then(|(property, value)| Some((property.trim(), value.trim().parse::<i64>().ok()?)))
+                .ok_or_else(|| QueryError::Record(line.to_string()))?;
+            properties.insert(property.to_string(), value);
+        }
+
+        Ok(Record {
+            name: name.trim().to_string(),
+            properties,
+        })
+    }
+}
+
+// How a record fares against each condition of a query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Evaluation {
+    pub satisfied: usize,
+    pub violated: usize,
+    pub unknown: usize, // conditions on properties the record does not have
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Name(usize, usize), // byte range in the query
+    Number(i64),
+    Operator(Operator),
+    Comma,
+}
+
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>, QueryError> {
+    let bytes = query.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let token = match bytes[i] {
+            byte if byte.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b',' => {
+                i += 1;
+                Token::Comma
+            }
+            b'<' | b'>' | b'=' | b'!' => {
+                let two = bytes.get(i + 1) == Some(&b'=');
+                let operator = match (bytes[i], two) {
+                    (b'<', false) => Operator::Less,
+                    (b'<', true) => Operator::LessOrEqual,
+                    (b'>', false) => Operator::Greater,
+                    (b'>', true) => Operator::GreaterOrEqual,
+                    (b'=', _) => Operator::Equal,
+                    (b'!', true) => Operator::NotEqual,
+                    _ => {
+                        return Err(QueryError::Parse {
+                            position: i,
+                            message: "Expected '!='".to_string(),
+                        });
+                    }
+                };
+                i += if two { 2 } else { 1 };
+                Token::Operator(operator)
+            }
+            b'-' | b'0'..=b'9' => {
+                i += 1;
+                while i < bytes.len() && bytes[i].is_ascii_digit() {
+                    i += 1;
+                }
+                let number = query[start..i].parse().map_err(|_| QueryError::Parse {
+                    position: start,
+                    message: format!("Invalid number '{}'", &query[start..i]),
+                })?;
+                Token::Number(number)
+            }
+            byte if byte.is_ascii_alphabetic() || byte == b'_' => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                Token::Name(start, i)
+            }
+            _ => {
+                let found = query[i..].chars().next().unwrap();
+                return Err(QueryError::Parse {
+                    position: i,
+                    message: format!("Unexpected '{}'", found),
+                });
+            }
+        };
+        tokens.push((start, token));
+    }
+
+    Ok(tokens)
+}
+
+// A comma separated list of conditions that must all hold, like
+// "cats > 7, trees > 3, pomeranians < 3, akitas = 0". Properties a record
+// does not mention are unknown rather than failing the match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    conditions: Vec<Condition>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let tokens = tokenize(query)?;
+        let error = |index: usize, expected: &str| QueryError::Parse {
+            position: tokens.get(index).map_or(query.len(), |&(position, _)| position),
+            message: format!("Expected {}", expected),
+        };
+
+        let mut conditions = Vec::new();
+        let mut index = 0;
+        loop {
+            let Some(&(_, Token::Name(start, end))) = tokens.get(index) else {
+                return Err(error(index, "a property name"));
+            };
+            let Some(&(_, Token::Operator(operator))) = tokens.get(index + 1) else {
+                return Err(error(index + 1, "a comparison"));
+            };
+            let Some(&(_, Token::Number(value))) = tokens.get(index + 2) else {
+                return Err(error(index + 2, "a number"));
+            };
+            conditions.push(Condition {
+                property: query[start..end].to_string(),
+                operator,
+                value,
+            });
+
+            index += 3;
+            match tokens.get(index) {
+                Some((_, Token::Comma)) => index += 1,
+                Some(_) => return Err(error(index, "','")),
+                None => break,
+            }
+        }
+
+        Ok(Query { conditions })
+    }
+
+    pub fn conditions(&self) -> &[Condition] {
+        &self.conditions
+    }
+
+    pub fn evaluate(&self, record: &Record) -> Evaluation {
+        let mut evaluation = Evaluation::default();
+        for condition in &self.conditions {
+            match record.properties.get(&condition.property) {
+                None => evaluation.unknown += 1,
+                Some(&value) if condition.operator.holds(value, condition.value) => evaluation.satisfied += 1,
+                Some(_) => evaluation.violated += 1,
+            }
+        }
+
+        evaluation
+    }
+
+    // Whether nothing the record says contradicts the query
+    pub fn matches(&self, record: &Record) -> bool {
+        self.evaluate(record).violated == 0
+    }
+
+    pub fn filter<'a>(&self, records: &'a [Record]) -> Vec<&'a Record> {
+        records.iter().filter(|record| self.matches(record)).collect()
+    }
+
+    // Record indices from the closest fit down: most conditions satisfied,
+    // then fewest violated, then input order
+    pub fn rank(&self, records: &[Record]) -> Vec<(usize, Evaluation)> {
+        let mut ranking = records
+            .iter()
+            .map(|record| self.evaluate(record))
+            .enumerate()
+            .collect::<Vec<(usize, Evaluation)>>();
+        ranking.sort_by_key(|&(index, evaluation)| (std::cmp::Reverse(evaluation.satisfied), evaluation.violated, index));
+        ranking
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conditions = self.conditions.iter().map(|condition| condition.to_string()).collect::<Vec<String>>();
+        write!(f, "{}", conditions.join(", "))
+    }
+}
+
+#[test]
+fn test_parse_query() {
+    let query = Query::parse("cats > 7, trees>=3,pomeranians < 3, akitas = 0, cars != -2").unwrap();
+    assert_eq!(query.conditions().len(), 5);
+    assert_eq!(
+        query.conditions()[1],
+        Condition {
+            property: "trees".to_string(),
+            operator: Operator::GreaterOrEqual,
+            value: 3,
+        }
+    );
+    assert_eq!(query.to_string(), "cats > 7, trees >= 3, pomeranians < 3, akitas = 0, cars != -2");
+    assert_eq!(Query::parse(&query.to_string()), Ok(query));
+
+    let error = |query: &str| Query::parse(query).unwrap_err().to_string();
+    assert_eq!(error(""), "Expected a property name at position 0");
+    assert_eq!(error("cats 7"), "Expected a comparison at position 5");
+    assert_eq!(error("cats > dogs"), "Expected a number at position 7");
+    assert_eq!(error("cats > 7,"), "Expected a property name at position 9");
+    assert_eq!(error("cats > 7 trees < 1"), "Expected ',' at position 9");
+    assert_eq!(error("cats ! 7"), "Expected '!=' at position 5");
+    assert_eq!(error("cats > 7; dogs"), "Unexpected ';' at position 8");
+}
+
+#[test]
+fn test_match_and_rank_records() {
+    let records = ["Sue 1: cats: 8, trees: 2", "Sue 2: goldfish: 9", "Sue 3: cats: 9, trees: 4, akitas: 0", "Sue 4: cats: 1"]
+        .iter()
+        .map(|line| Record::parse(line).unwrap())
+        .collect::<Vec<Record>>();
+    assert_eq!(records[2].properties["akitas"], 0);
+
+    let query = Query::parse("cats > 7, trees > 3, akitas = 0").unwrap();
+    let names = query.filter(&records).iter().map(|record| record.name.as_str()).collect::<Vec<&str>>();
+    assert_eq!(names, ["Sue 2", "Sue 3"]);
+    assert_eq!(
+        query.evaluate(&records[0]),
+        Evaluation {
+            satisfied: 1,
+            violated: 1,
+            unknown: 1,
+        }
+    );
+
+    let strict = Query::parse("cats > 8, trees > 3, akitas = 1").unwrap();
+    assert_eq!(strict.filter(&records).len(), 1);
+    let ranking = strict.rank(&records).iter().map(|&(index, _)| index).collect::<Vec<usize>>();
+    assert_eq!(ranking, [2, 1, 3, 0]);
+    assert!(Record::parse("Sue 5 cats 3").is_err());
+}
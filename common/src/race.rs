@@ -0,0 +1,289 @@
+use std::io::Write;
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RaceError {
+    #[error("Invalid reindeer: {0}")]
+    Parse(String),
+    #[error("{0} never flies or rests, so its race never moves on")]
+    EmptyCycle(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reindeer {
+    pub name: String,
+    pub speed: u64,
+    pub fly_time: u64,
+    pub rest_time: u64,
+}
+
+impl Reindeer {
+    // "Comet can fly 14 km/s for 10 seconds, but then must rest for 127 seconds."
+    pub fn parse(line: &str) -> Result<Self, RaceError> {
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        let number = |index: usize| {
+            parts
+                .get(index)
+                .and_then(|part| part.parse::<u64>().ok())
+                .ok_or_else(|| RaceError::Parse(line.to_string()))
+        };
+        let reindeer = Reindeer {
+            name: parts.first().ok_or_else(|| RaceError::Parse(line.to_string()))?.to_string(),
+            speed: number(3)?,
+            fly_time: number(6)?,
+            rest_time: number(13)?,
+        };
+        if reindeer.cycle() == 0 {
+            return Err(RaceError::EmptyCycle(reindeer.name));
+        }
+
+        Ok(reindeer)
+    }
+
+    fn cycle(&self) -> u64 {
+        self.fly_time + self.rest_time
+    }
+
+    pub fn distance_at(&self, time: u64) -> u64 {
+        let cycles = time / self.cycle();
+        let flown = cycles * self.fly_time + (time % self.cycle()).min(self.fly_time);
+        self.speed * flown
+    }
+
+    // Speed during the second that starts at `time`
+    fn speed_after(&self, time: u64) -> u64 {
+        if time % self.cycle() < self.fly_time { self.speed } else { 0 }
+    }
+
+    // The first time after `time` at which the reindeer starts or stops flying
+    fn next_switch(&self, time: u64) -> u64 {
+        let phase = time % self.cycle();
+        if phase < self.fly_time {
+            time + self.fly_time - phase
+        }
+        else {
+            time + self.cycle() - phase
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scoreboard {
+    pub duration: u64,
+    pub points: Vec<u64>, // indexed like the race's reindeer
+}
+
+impl Scoreboard {
+    pub fn best(&self) -> u64 {
+        self.points.iter().copied().max().unwrap_or(0)
+    }
+
+    // Everyone sharing the most points
+    pub fn winners(&self) -> Vec<usize> {
+        let best = self.best();
+        (0..self.points.len()).filter(|&i| self.points[i] == best).collect()
+    }
+
+    // Reindeer indices from most to fewest points, ties in race order
+    pub fn standings(&self) -> Vec<usize> {
+        let mut order = (0..self.points.len()).collect::<Vec<usize>>();
+        order.sort_by_key(|&i| std::cmp::Reverse(self.points[i]));
+        order
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Race {
+    reindeer: Vec<Reindeer>,
+}
+
+impl Race {
+    pub fn new(reindeer: Vec<Reindeer>) -> Self {
+        Race { reindeer }
+    }
+
+    pub fn parse(input: &str) -> Result<Self, RaceError> {
+        let reindeer = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(Reindeer::parse)
+            .collect::<Result<Vec<Reindeer>, RaceError>>()?;
+
+        Ok(Race::new(reindeer))
+    }
+
+    pub fn reindeer(&self) -> &[Reindeer] {
+        &self.reindeer
+    }
+
+    pub fn distances_at(&self, time: u64) -> Vec<u64> {
+        self.reindeer.iter().map(|reindeer| reindeer.distance_at(time)).collect()
+    }
+
+    // Everyone tied for the lead at `time`
+    pub fn leaders_at(&self, time: u64) -> Vec<usize> {
+        let distances = self.distances_at(time);
+        let lead = distances.iter().copied().max().unwrap_or(0);
+        (0..distances.len()).filter(|&i| distances[i] == lead).collect()
+    }
+
+    // Points after `duration` seconds, one per second for each reindeer tied
+    // for the lead. Between two moments where anyone starts or stops flying
+    // every distance is a straight line, so each of those stretches only
+    // needs to look at the seconds where the leading line changes.
+    pub fn scoreboard(&self, duration: u64) -> Scoreboard {
+        let mut points = vec![0; self.reindeer.len()];
+        let mut start = 0;
+        while start < duration && !self.reindeer.is_empty() {
+            let end = self
+                .reindeer
+                .iter()
+                .map(|reindeer| reindeer.next_switch(start))
+                .min()
+                .unwrap()
+                .min(duration);
+            self.score_stretch(start, end, &mut points);
+            start = end;
+        }
+
+        Scoreboard { duration, points }
+    }
+
+    // Awards the points for seconds start + 1 ..= end, with every reindeer
+    // moving at a constant speed throughout
+    fn score_stretch(&self, start: u64, end: u64, points: &mut [u64]) {
+        let lines = self
+            .reindeer
+            .iter()
+            .map(|reindeer| (reindeer.distance_at(start), reindeer.speed_after(start)))
+            .collect::<Vec<(u64, u64)>>();
+        let at = |i: usize, time: u64| lines[i].0 + lines[i].1 * (time - start);
+
+        let mut time = start + 1;
+        while time <= end {
+            let lead = (0..lines.len()).map(|i| at(i, time)).max().unwrap();
+            let leaders = (0..lines.len()).filter(|&i| at(i, time) == lead).collect::<Vec<usize>>();
+            let speed = leaders.iter().map(|&i| lines[i].1).max().unwrap();
+
+            // The fastest leaders keep the lead alone until someone faster
+            // catches up with them
+            let mut next = end + 1;
+            for (i, &(_, other_speed)) in lines.iter().enumerate() {
+                if other_speed > speed {
+                    let gap = lead - at(i, time);
+                    next = next.min(time + gap.div_ceil(other_speed - speed));
+                }
+            }
+
+            for &i in &leaders {
+                points[i] += 1;
+                if lines[i].1 == speed {
+                    points[i] += next - time - 1;
+                }
+            }
+            time = next;
+        }
+    }
+
+    // One row per reindeer and second: distance, points so far and position,
+    // where reindeer that are level share a position
+    pub fn write_timeline_csv<W: Write>(&self, duration: u64, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "second,reindeer,distance,points,position")?;
+        let mut points = vec![0; self.reindeer.len()];
+        for time in 1..=duration {
+            let distances = self.distances_at(time);
+            let lead = distances.iter().copied().max().unwrap_or(0);
+            for (i, &distance) in distances.iter().enumerate() {
+                if distance == lead {
+                    points[i] += 1;
+                }
+            }
+            for (i, reindeer) in self.reindeer.iter().enumerate() {
+                let position = 1 + distances.iter().filter(|&&distance| distance > distances[i]).count();
+                writeln!(writer, "{},{},{},{},{}", time, reindeer.name, distances[i], points[i], position)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "Comet can fly 14 km/s for 10 seconds, but then must rest for 127 seconds.
+Dancer can fly 16 km/s for 11 seconds, but then must rest for 162 seconds.";
+
+#[test]
+fn test_puzzle_example() {
+    let race = Race::parse(EXAMPLE).unwrap();
+    assert_eq!(race.distances_at(1), vec![14, 16]);
+    assert_eq!(race.distances_at(10), vec![140, 160]);
+    assert_eq!(race.distances_at(11), vec![140, 176]);
+    assert_eq!(race.distances_at(1000), vec![1120, 1056]);
+    assert_eq!(race.leaders_at(1000), vec![0]);
+
+    let scoreboard = race.scoreboard(1000);
+    assert_eq!(scoreboard.points, vec![312, 689]);
+    assert_eq!(scoreboard.winners(), vec![1]);
+    assert_eq!(scoreboard.standings(), vec![1, 0]);
+    assert_eq!(race.scoreboard(0).points, vec![0, 0]);
+
+    assert_eq!(
+        Reindeer::parse("Comet can fly fast"),
+        Err(RaceError::Parse("Comet can fly fast".to_string()))
+    );
+    assert_eq!(
+        Reindeer::parse("Idle can fly 3 km/s for 0 seconds, but then must rest for 0 seconds."),
+        Err(RaceError::EmptyCycle("Idle".to_string()))
+    );
+}
+
+#[test]
+fn test_scoreboard_matches_simulation() {
+    let mut seed = 0x2015_0014_u64;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+
+    for _ in 0..200 {
+        let mut reindeer = (0..1 + random(5))
+            .map(|i| Reindeer {
+                name: format!("R{i}"),
+                speed: 1 + random(6),
+                fly_time: random(8),
+                rest_time: 1 + random(8),
+            })
+            .collect::<Vec<Reindeer>>();
+        // Identical reindeer tie for the whole race
+        if random(3) == 0 {
+            reindeer.push(reindeer[0].clone());
+        }
+        let race = Race::new(reindeer);
+        let duration = random(400);
+
+        let mut expected = vec![0; race.reindeer().len()];
+        for time in 1..=duration {
+            for i in race.leaders_at(time) {
+                expected[i] += 1;
+            }
+        }
+        assert_eq!(race.scoreboard(duration).points, expected, "{:?} for {}", race.reindeer(), duration);
+    }
+}
+
+#[test]
+fn test_timeline_csv() {
+    let race = Race::parse(EXAMPLE).unwrap();
+    let mut csv = Vec::new();
+    race.write_timeline_csv(140, &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines = csv.lines().collect::<Vec<&str>>();
+    assert_eq!(lines.len(), 1 + 140 * 2);
+    assert_eq!(lines[0], "second,reindeer,distance,points,position");
+    assert_eq!(lines[1..3], ["1,Comet,14,0,2", "1,Dancer,16,1,1"]);
+    assert_eq!(lines[lines.len() - 2..], ["140,Comet,182,1,1", "140,Dancer,176,139,2"]);
+
+    let last = race.scoreboard(140);
+    assert_eq!(last.points, vec![1, 139]);
+}
@@ -0,0 +1,355 @@
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RecipeError {
+    #[error("Invalid ingredient: {0}")]
+    Parse(String),
+    #[error("Unknown property '{0}'")]
+    UnknownProperty(String),
+    #[error("Ingredient '{0}' does not list the same properties as the others")]
+    MismatchedProperties(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ingredient {
+    pub name: String,
+    pub properties: Vec<i64>, // per teaspoon, in the optimizer's property order
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exactly,
+    AtMost,
+    AtLeast,
+}
+
+impl Bound {
+    fn allows(self, value: i64, limit: i64) -> bool {
+        match self {
+            Bound::Exactly => value == limit,
+            Bound::AtMost => value <= limit,
+            Bound::AtLeast => value >= limit,
+        }
+    }
+
+    // Whether some value in min..=max is allowed
+    fn reachable(self, min: i64, max: i64, limit: i64) -> bool {
+        match self {
+            Bound::Exactly => (min..=max).contains(&limit),
+            Bound::AtMost => min <= limit,
+            Bound::AtLeast => max >= limit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraint {
+    pub property: usize,
+    pub bound: Bound,
+    pub limit: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub amounts: Vec<i64>, // teaspoons of each ingredient
+    pub totals: Vec<i64>,  // each property summed over the recipe
+    pub score: i128,
+}
+
+// Splits a fixed number of teaspoons between ingredients to maximize the
+// product of the scored property totals, each clamped at zero, subject to
+// bounds on any property.
+#[derive(Debug, Clone)]
+pub struct RecipeOptimizer {
+    properties: Vec<String>,
+    ingredients: Vec<Ingredient>,
+    scored: Vec<bool>,
+    constraints: Vec<Constraint>,
+    total: i64,
+}
+
+impl RecipeOptimizer {
+    pub fn new(properties: Vec<String>, total: i64) -> Self {
+        RecipeOptimizer {
+            scored: vec![true; properties.len()],
+            properties,
+            ingredients: Vec::new(),
+            constraints: Vec::new(),
+            total,
+        }
+    }
+
+    // "Sugar: capacity 3, durability 0, flavor 0, texture -3, calories 2" per
+    // line, scoring every property and using 100 teaspoons
+    pub fn parse(input: &str) -> Result<Self, RecipeError> {
+        let mut optimizer: Option<RecipeOptimizer> = None;
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            let (name, rest) = line.split_once(':').ok_or_else(|| RecipeError::Parse(line.to_string()))?;
+            let mut names = Vec::new();
+            let mut values = Vec::new();
+            for property in rest.split(',') {
+                let (property, value) = property
+                    .trim()
+                    .split_once(' ')
+                    .and_then(|(property, value)| Some((property, value.trim().parse::<i64>().ok()?)))
+                    .ok_or_else(|| RecipeError::Parse(line.to_string()))?;
+                names.push(property.to_string());
+                values.push(value);
+            }
+
+            let optimizer = optimizer.get_or_insert_with(|| RecipeOptimizer::new(names.clone(), 100));
+            if optimizer.properties != names {
+                return Err(RecipeError::MismatchedProperties(name.trim().to_string()));
+            }
+            optimizer.add_ingredient(name.trim(), values)?;
+        }
+
+        Ok(optimizer.unwrap_or_else(|| RecipeOptimizer::new(Vec::new(), 100)))
+    }
+
+    pub fn add_ingredient(&mut self, name: &str, properties: Vec<i64>) -> Result<(), RecipeError> {
+        if properties.len() != self.properties.len() {
+            return Err(RecipeError::MismatchedProperties(name.to_string()));
+        }
+
+        self.ingredients.push(Ingredient {
+            name: name.to_string(),
+            properties,
+        });
+        Ok(())
+    }
+
+    pub fn ingredients(&self) -> &[Ingredient] {
+        &self.ingredients
+    }
+
+    pub fn properties(&self) -> &[String] {
+        &self.properties
+    }
+
+    pub fn set_total(&mut self, total: i64) {
+        self.total = total;
+    }
+
+    fn property(&self, name: &str) -> Result<usize, RecipeError> {
+        self.properties
+            .iter()
+            .position(|property| property == name)
+            .ok_or_else(|| RecipeError::UnknownProperty(name.to_string()))
+    }
+
+    // Leaves a property out of the score, like calories in Santa's cookies
+    pub fn ignore(&mut self, property: &str) -> Result<(), RecipeError> {
+        let property = self.property(property)?;
+        self.scored[property] = false;
+        Ok(())
+    }
+
+    pub fn constrain(&mut self, property: &str, bound: Bound, limit: i64) -> Result<(), RecipeError> {
+        let property = self.property(property)?;
+        self.constraints.push(Constraint { property, bound, limit });
+        Ok(())
+    }
+
+    fn score(&self, totals: &[i64]) -> i128 {
+        totals
+            .iter()
+            .zip(&self.scored)
+            .filter(|&(_, &scored)| scored)
+            .map(|(&total, _)| i128::from(total.max(0)))
+            .product()
+    }
+
+    // The best recipe, or None if no split meets the constraints. A depth
+    // first search over the amounts that cuts a branch as soon as the
+    // remaining teaspoons cannot satisfy a constraint, or when even the best
+    // value of every scored property taken on its own cannot beat the best
+    // recipe found so far.
+    pub fn optimize(&self) -> Option<Solution> {
+        if self.ingredients.is_empty() || self.total < 0 {
+            return None;
+        }
+
+        // Smallest and largest value per teaspoon among ingredients i.. for
+        // every property
+        let n = self.ingredients.len();
+        let mut ranges = vec![vec![(i64::MAX, i64::MIN); self.properties.len()]; n + 1];
+        for i in (0..n).rev() {
+            ranges[i] = ranges[i + 1]
+                .iter()
+                .zip(&self.ingredients[i].properties)
+                .map(|(&(min, max), &value)| (min.min(value), max.max(value)))
+                .collect();
+        }
+
+        let mut search = Search {
+            optimizer: self,
+            ranges,
+            amounts: vec![0; n],
+            totals: vec![0; self.properties.len()],
+            best: None,
+        };
+        search.run(0, self.total);
+        search.best
+    }
+}
+
+struct Search<'a> {
+    optimizer: &'a RecipeOptimizer,
+    ranges: Vec<Vec<(i64, i64)>>,
+    amounts: Vec<i64>,
+    totals: Vec<i64>,
+    best: Option<Solution>,
+}
+
+impl Search<'_> {
+    fn add(&mut self, ingredient: usize, amount: i64) {
+        self.amounts[ingredient] += amount;
+        for (total, &value) in self.totals.iter_mut().zip(&self.optimizer.ingredients[ingredient].properties) {
+            *total += value * amount;
+        }
+    }
+
+    fn run(&mut self, ingredient: usize, remaining: i64) {
+        let optimizer = self.optimizer;
+        let ranges = &self.ranges[ingredient];
+        let feasible = optimizer.constraints.iter().all(|constraint| {
+            let (min, max) = ranges[constraint.property];
+            let total = self.totals[constraint.property];
+            constraint
+                .bound
+                .reachable(total + min * remaining, total + max * remaining, constraint.limit)
+        });
+        if !feasible {
+            return;
+        }
+
+        let bound = self
+            .totals
+            .iter()
+            .zip(ranges)
+            .zip(&optimizer.scored)
+            .filter(|&(_, &scored)| scored)
+            .map(|((&total, &(_, max)), _)| i128::from((total + max * remaining).max(0)))
+            .product::<i128>();
+        if self.best.as_ref().is_some_and(|best| bound <= best.score) {
+            return;
+        }
+
+        if ingredient + 1 == self.amounts.len() {
+            self.add(ingredient, remaining);
+            let allowed = optimizer
+                .constraints
+                .iter()
+                .all(|constraint| constraint.bound.allows(self.totals[constraint.property], constraint.limit));
+            let score = optimizer.score(&self.totals);
+            if allowed && self.best.as_ref().is_none_or(|best| score > best.score) {
+                self.best = Some(Solution {
+                    amounts: self.amounts.clone(),
+                    totals: self.totals.clone(),
+                    score,
+                });
+            }
+            self.add(ingredient, -remaining);
+            return;
+        }
+
+        for amount in 0..=remaining {
+            self.add(ingredient, amount);
+            self.run(ingredient + 1, remaining - amount);
+            self.add(ingredient, -amount);
+        }
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "Butterscotch: capacity -1, durability -2, flavor 6, texture 3, calories 8
+Cinnamon: capacity 2, durability 3, flavor -2, texture -1, calories 3";
+
+#[cfg(test)]
+fn brute_force(optimizer: &RecipeOptimizer) -> Option<i128> {
+    fn splits(parts: usize, total: i64) -> Vec<Vec<i64>> {
+        if parts == 1 {
+            return vec![vec![total]];
+        }
+        (0..=total)
+            .flat_map(|amount| {
+                splits(parts - 1, total - amount).into_iter().map(move |mut rest| {
+                    rest.push(amount);
+                    rest
+                })
+            })
+            .collect()
+    }
+
+    splits(optimizer.ingredients.len(), optimizer.total)
+        .into_iter()
+        .filter_map(|amounts| {
+            let totals = (0..optimizer.properties.len())
+                .map(|p| (0..amounts.len()).map(|i| amounts[i] * optimizer.ingredients[i].properties[p]).sum())
+                .collect::<Vec<i64>>();
+            optimizer
+                .constraints
+                .iter()
+                .all(|constraint| constraint.bound.allows(totals[constraint.property], constraint.limit))
+                .then(|| optimizer.score(&totals))
+        })
+        .max()
+}
+
+#[test]
+fn test_puzzle_example() {
+    let mut optimizer = RecipeOptimizer::parse(EXAMPLE).unwrap();
+    optimizer.ignore("calories").unwrap();
+    let best = optimizer.optimize().unwrap();
+    assert_eq!(best.amounts, vec![44, 56]);
+    assert_eq!(best.score, 62842880);
+
+    optimizer.constrain("calories", Bound::Exactly, 500).unwrap();
+    let best = optimizer.optimize().unwrap();
+    assert_eq!(best.amounts, vec![40, 60]);
+    assert_eq!(best.totals[4], 500);
+    assert_eq!(best.score, 57600000);
+
+    optimizer.constrain("calories", Bound::AtLeast, 900).unwrap();
+    assert_eq!(optimizer.optimize(), None);
+    assert_eq!(optimizer.ignore("sweetness"), Err(RecipeError::UnknownProperty("sweetness".to_string())));
+    assert_eq!(
+        RecipeOptimizer::parse("A: x 1, y 2\nB: x 1").unwrap_err(),
+        RecipeError::MismatchedProperties("B".to_string())
+    );
+}
+
+#[test]
+fn test_optimize_matches_brute_force() {
+    let mut seed = 0x2015_0015_u64;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((seed >> 33) % bound) as i64
+    };
+
+    let bounds = [Bound::Exactly, Bound::AtMost, Bound::AtLeast];
+    for _ in 0..150 {
+        let properties = (0..1 + random(4)).map(|p| format!("p{p}")).collect::<Vec<String>>();
+        let mut optimizer = RecipeOptimizer::new(properties.clone(), 5 + random(20));
+        for i in 0..1 + random(4) {
+            let values = properties.iter().map(|_| random(13) - 5).collect();
+            optimizer.add_ingredient(&format!("i{i}"), values).unwrap();
+        }
+        for _ in 0..random(3) {
+            let property = &properties[random(properties.len() as u64) as usize];
+            let bound = bounds[random(3) as usize];
+            optimizer.constrain(property, bound, random(60) - 20).unwrap();
+        }
+        if properties.len() > 1 && random(2) == 0 {
+            optimizer.ignore(&properties[0]).unwrap();
+        }
+
+        let best = optimizer.optimize();
+        assert_eq!(best.as_ref().map(|best| best.score), brute_force(&optimizer), "{optimizer:?}");
+        if let Some(best) = best {
+            assert_eq!(best.amounts.iter().sum::<i64>(), optimizer.total);
+            assert_eq!(best.score, optimizer.score(&best.totals));
+        }
+    }
+}
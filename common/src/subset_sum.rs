@@ -0,0 +1,256 @@
+use thiserror::Error;
+
+// Largest counting table `SubsetSums::new` builds, in u128 cells (64 MiB)
+pub const MAX_TABLE_CELLS: u128 = 1 << 22;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SubsetSumError {
+    #[error("Reaching {target} with up to {max_size} items needs {cells} table cells, more than {MAX_TABLE_CELLS}")]
+    TableTooLarge { target: u64, max_size: usize, cells: u128 },
+}
+
+// Counts the subsets of `items` that add up exactly to `target`, split by
+// how many items they use. Counts too large for a u128 saturate at
+// u128::MAX and are reported as None.
+#[derive(Debug, Clone)]
+pub struct SubsetSums {
+    items: Vec<u64>,
+    target: u64,
+    by_size: Vec<u128>,
+}
+
+impl SubsetSums {
+    // ways[k][v] is the number of subsets of the items seen so far with k
+    // items summing to v. No subset can use more items than the smallest ones
+    // that fit together, which bounds k well below the item count for most
+    // inputs: O(n * k * target) time and O(k * target) memory, refused when
+    // the table would exceed MAX_TABLE_CELLS.
+    pub fn new(items: &[u64], target: u64) -> Result<Self, SubsetSumError> {
+        let mut sorted = items.to_vec();
+        sorted.sort_unstable();
+        let mut max_size = 0;
+        let mut volume = 0u64;
+        for &item in &sorted {
+            volume = volume.saturating_add(item);
+            if volume > target {
+                break;
+            }
+            max_size += 1;
+        }
+
+        let cells = (max_size as u128 + 1) * (target as u128 + 1);
+        if cells > MAX_TABLE_CELLS {
+            return Err(SubsetSumError::TableTooLarge { target, max_size, cells });
+        }
+
+        let width = target as usize + 1;
+        let mut ways = vec![vec![0u128; width]; max_size + 1];
+        ways[0][0] = 1;
+        for (seen, &item) in items.iter().enumerate() {
+            if item > target {
+                continue;
+            }
+            let item = item as usize;
+            // Larger sizes first so this item is counted at most once per
+            // subset, also when it is zero
+            for size in (1..=max_size.min(seen + 1)).rev() {
+                let (smaller, larger) = ways.split_at_mut(size);
+                let (from, to) = (&smaller[size - 1], &mut larger[0]);
+                for volume in (item..width).rev() {
+                    to[volume] = to[volume].saturating_add(from[volume - item]);
+                }
+            }
+        }
+
+        Ok(SubsetSums {
+            items: items.to_vec(),
+            target,
+            by_size: ways.iter().map(|row| row[target as usize]).collect(),
+        })
+    }
+
+    // Number of combinations by item count, from 0 items up
+    pub fn counts_by_size(&self) -> Vec<Option<u128>> {
+        self.by_size.iter().map(|&count| (count != u128::MAX).then_some(count)).collect()
+    }
+
+    pub fn count(&self, size: usize) -> Option<u128> {
+        match self.by_size.get(size) {
+            Some(&count) => (count != u128::MAX).then_some(count),
+            None => Some(0),
+        }
+    }
+
+    pub fn total(&self) -> Option<u128> {
+        self.by_size
+            .iter()
+            .try_fold(0u128, |total, &count| total.checked_add(count).filter(|&total| total != u128::MAX))
+    }
+
+    // Fewest items that reach the target, if any combination does
+    pub fn min_size(&self) -> Option<usize> {
+        self.by_size.iter().position(|&count| count > 0)
+    }
+
+    // Every combination with the fewest items, as sorted item indices.
+    // fewest_i[v] is the fewest of items i.. summing to v, u32::MAX if they
+    // can't, which no count of a real item list reaches; a partial
+    // combination that still needs r items for v after choosing from items
+    // before i is on a minimal one exactly when fewest_i[v] == r, so the walk
+    // over the items keeps only the branches that end in a combination.
+    // Building the rows back to front but walking front to back, it keeps
+    // every block-th row and rebuilds one block at a time from the next
+    // checkpoint: O(sqrt(n) * target) memory for about twice the work.
+    pub fn minimal_combinations(&self) -> Vec<Vec<usize>> {
+        let Some(size) = self.min_size() else {
+            return Vec::new();
+        };
+
+        let n = self.items.len();
+        let block = (1..).find(|block| block * block >= n).unwrap();
+        let mut row = vec![u32::MAX; self.target as usize + 1];
+        row[0] = 0;
+        let mut checkpoints = vec![Vec::new(); n.div_ceil(block) + 1];
+        for i in (0..=n).rev() {
+            if i % block == 0 || i == n {
+                checkpoints[i.div_ceil(block)] = row.clone();
+            }
+            if i > 0 {
+                self.add_item(&mut row, i - 1);
+            }
+        }
+
+        // Partial combinations still short of a volume, in the order of
+        // their sorted indices
+        let mut partial = vec![(self.target as usize, Vec::with_capacity(size))];
+        for start in (0..n).step_by(block) {
+            let end = (start + block).min(n);
+            // rows[i - start] is fewest_(i + 1)
+            let mut rows = vec![checkpoints[end.div_ceil(block)].clone()];
+            for i in (start + 1..end).rev() {
+                let mut row = rows.last().unwrap().clone();
+                self.add_item(&mut row, i);
+                rows.push(row);
+            }
+            rows.reverse();
+
+            for (i, fewest) in (start..end).zip(&rows) {
+                let item = self.items[i];
+                let mut next = Vec::with_capacity(partial.len());
+                for (volume, chosen) in partial {
+                    let needed = (size - chosen.len()) as u32;
+                    if needed > 0 && item <= volume as u64 && fewest[volume - item as usize] == needed - 1 {
+                        let mut taken = chosen.clone();
+                        taken.push(i);
+                        next.push((volume - item as usize, taken));
+                    }
+                    if fewest[volume] == needed {
+                        next.push((volume, chosen));
+                    }
+                }
+                partial = next;
+            }
+        }
+
+        partial.into_iter().map(|(_, chosen)| chosen).collect()
+    }
+
+    // Turns fewest_(index + 1) into fewest_index in place
+    fn add_item(&self, fewest: &mut [u32], index: usize) {
+        let item = self.items[index];
+        if item > self.target {
+            return;
+        }
+        let item = item as usize;
+        // Larger volumes first so the item is used at most once
+        for volume in (item..fewest.len()).rev() {
+            fewest[volume] = fewest[volume].min(fewest[volume - item].saturating_add(1));
+        }
+    }
+}
+
+#[cfg(test)]
+fn brute_force(items: &[u64], target: u64) -> Vec<Vec<usize>> {
+    (0u32..1 << items.len())
+        .map(|mask| (0..items.len()).filter(|&i| mask >> i & 1 == 1).collect::<Vec<usize>>())
+        .filter(|subset| subset.iter().map(|&i| items[i]).sum::<u64>() == target)
+        .collect()
+}
+
+#[test]
+fn test_puzzle_example() {
+    let sums = SubsetSums::new(&[20, 15, 10, 5, 5], 25).unwrap();
+    assert_eq!(sums.total(), Some(4));
+    assert_eq!(sums.counts_by_size(), vec![Some(0), Some(0), Some(3), Some(1)]);
+    assert_eq!(sums.min_size(), Some(2));
+    assert_eq!(sums.minimal_combinations(), vec![vec![0, 3], vec![0, 4], vec![1, 2]]);
+    assert_eq!(sums.count(7), Some(0));
+
+    let none = SubsetSums::new(&[4, 6], 5).unwrap();
+    assert_eq!(none.total(), Some(0));
+    assert_eq!(none.min_size(), None);
+    assert!(none.minimal_combinations().is_empty());
+}
+
+#[test]
+fn test_subset_sums_match_brute_force() {
+    let mut seed = 0x2015_0017_u64;
+    let mut random = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+
+    for _ in 0..200 {
+        let items = (0..random(13)).map(|_| random(12)).collect::<Vec<u64>>();
+        let target = random(30);
+        let sums = SubsetSums::new(&items, target).unwrap();
+        let subsets = brute_force(&items, target);
+
+        for (size, count) in sums.counts_by_size().into_iter().enumerate() {
+            let expected = subsets.iter().filter(|subset| subset.len() == size).count() as u128;
+            assert_eq!(count, Some(expected), "{items:?} to {target} with {size}");
+        }
+        assert_eq!(sums.total(), Some(subsets.len() as u128));
+
+        let min_size = subsets.iter().map(|subset| subset.len()).min();
+        let mut minimal = subsets.into_iter().filter(|subset| Some(subset.len()) == min_size).collect::<Vec<_>>();
+        minimal.sort();
+        assert_eq!(sums.min_size(), min_size);
+        assert_eq!(sums.minimal_combinations(), minimal, "{items:?} to {target}");
+    }
+}
+
+#[test]
+fn test_large_inputs() {
+    // Two hundred containers of 1..=20 liters, and counts past u128
+    let items = (0..200).map(|i| 1 + i % 20).collect::<Vec<u64>>();
+    let sums = SubsetSums::new(&items, 1000).unwrap();
+    assert_eq!(sums.min_size(), Some(57));
+    assert_eq!(sums.count(57), Some(5581190));
+    assert_eq!(sums.total(), None);
+
+    // Only the ten 19s and ten 20s make 390 from twenty containers
+    let sums = SubsetSums::new(&items, 390).unwrap();
+    let expected = (0..200).filter(|i| i % 20 >= 18).collect::<Vec<usize>>();
+    assert_eq!(sums.minimal_combinations(), vec![expected]);
+
+    let zeros = SubsetSums::new(&[0; 130], 0).unwrap();
+    assert_eq!(zeros.count(1), Some(130));
+    assert_eq!(zeros.total(), None);
+    assert_eq!(zeros.minimal_combinations(), vec![Vec::<usize>::new()]);
+}
+
+#[test]
+fn test_large_targets() {
+    // A single row is cheap even for a large target, and huge items can't
+    // overflow the volume that bounds the sizes
+    let sums = SubsetSums::new(&[u64::MAX, 100_000, u64::MAX, 300_000], 400_000).unwrap();
+    assert_eq!(sums.counts_by_size(), vec![Some(0), Some(0), Some(1)]);
+    assert_eq!(sums.minimal_combinations(), vec![vec![1, 3]]);
+
+    assert_eq!(
+        SubsetSums::new(&[1; 10], 10_000_000).unwrap_err(),
+        SubsetSumError::TableTooLarge { target: 10_000_000, max_size: 10, cells: 110_000_011 }
+    );
+    assert!(SubsetSums::new(&[1], u64::MAX).is_err());
+}
@@ -0,0 +1,283 @@
+use thiserror::Error;
+
+pub mod debugger;
+pub mod optimizer;
+
+use optimizer::SuperInstruction;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum VmError {
+    #[error("Invalid instruction: {0}")]
+    Parse(String),
+    #[error("Unknown register '{0}'")]
+    UnknownRegister(String),
+    #[error("Jump from {pc} by {offset} leaves the program")]
+    JumpOutOfBounds { pc: usize, offset: i64 },
+    #[error("Register overflow at {pc}")]
+    Overflow { pc: usize },
+    #[error("Instruction limit of {0} reached")]
+    InstructionLimit(u64),
+    #[error("{given} register values given for {available} registers")]
+    TooManyRegisters { given: usize, available: usize },
+}
+
+// The 2015 day23 instruction set, registers are indices ('a' is 0)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Hlf(usize),      // register
+    Tpl(usize),      // register
+    Inc(usize),      // register
+    Jmp(i64),        // offset
+    Jie(usize, i64), // register, offset taken if the register is even
+    Jio(usize, i64), // register, offset taken if the register is one
+}
+
+pub fn register_name(register: usize) -> char {
+    (b'a' + register as u8) as char
+}
+
+fn parse_register(token: &str, num_registers: usize) -> Result<usize, VmError> {
+    let token = token.trim_end_matches(',');
+    match token.as_bytes() {
+        [c] if c.is_ascii_lowercase() && ((c - b'a') as usize) < num_registers => Ok((c - b'a') as usize),
+        _ => Err(VmError::UnknownRegister(token.to_string())),
+    }
+}
+
+impl Instruction {
+    pub fn parse(line: &str, num_registers: usize) -> Result<Instruction, VmError> {
+        let invalid = || VmError::Parse(line.to_string());
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        let offset = |token: &str| token.parse::<i64>().map_err(|_| invalid());
+        let instruction = match parts.as_slice() {
+            ["hlf", reg] => Instruction::Hlf(parse_register(reg, num_registers)?),
+            ["tpl", reg] => Instruction::Tpl(parse_register(reg, num_registers)?),
+            ["inc", reg] => Instruction::Inc(parse_register(reg, num_registers)?),
+            ["jmp", off] => Instruction::Jmp(offset(off)?),
+            ["jie", reg, off] => Instruction::Jie(parse_register(reg, num_registers)?, offset(off)?),
+            ["jio", reg, off] => Instruction::Jio(parse_register(reg, num_registers)?, offset(off)?),
+            _ => return Err(invalid()),
+        };
+
+        Ok(instruction)
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Instruction::Hlf(reg) => write!(f, "hlf {}", register_name(*reg)),
+            Instruction::Tpl(reg) => write!(f, "tpl {}", register_name(*reg)),
+            Instruction::Inc(reg) => write!(f, "inc {}", register_name(*reg)),
+            Instruction::Jmp(offset) => write!(f, "jmp {:+}", offset),
+            Instruction::Jie(reg, offset) => write!(f, "jie {}, {:+}", register_name(*reg), offset),
+            Instruction::Jio(reg, offset) => write!(f, "jio {}, {:+}", register_name(*reg), offset),
+        }
+    }
+}
+
+// A register machine over a compiled program. It halts when the program
+// counter moves exactly one past the last instruction; any other jump out of
+// the program is an error rather than a silent halt.
+#[derive(Debug, Clone)]
+pub struct Vm {
+    program: Vec<Instruction>,
+    fused: Vec<Option<SuperInstruction>>, // indexed by pc, empty until optimized
+    registers: Vec<u64>,
+    pc: usize,
+    steps: u64,
+}
+
+impl Vm {
+    pub fn new(program: Vec<Instruction>, num_registers: usize) -> Self {
+        Vm {
+            program,
+            fused: Vec::new(),
+            registers: vec![0; num_registers],
+            pc: 0,
+            steps: 0,
+        }
+    }
+
+    pub fn parse(input: &str, num_registers: usize) -> Result<Self, VmError> {
+        let program = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Instruction::parse(line, num_registers))
+            .collect::<Result<Vec<Instruction>, VmError>>()?;
+
+        Ok(Vm::new(program, num_registers))
+    }
+
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
+    pub fn registers(&self) -> &[u64] {
+        &self.registers
+    }
+
+    pub fn register(&self, register: usize) -> u64 {
+        self.registers[register]
+    }
+
+    pub fn set_register(&mut self, register: usize, value: u64) {
+        self.registers[register] = value;
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    // Instructions executed since the last reset
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.pc == self.program.len()
+    }
+
+    // Back to pc 0 with the given register values, the rest starting at zero
+    pub fn reset(&mut self, registers: &[u64]) -> Result<(), VmError> {
+        if registers.len() > self.registers.len() {
+            return Err(VmError::TooManyRegisters {
+                given: registers.len(),
+                available: self.registers.len(),
+            });
+        }
+
+        self.registers.iter_mut().for_each(|r| *r = 0);
+        self.registers[..registers.len()].copy_from_slice(registers);
+        self.pc = 0;
+        self.steps = 0;
+        Ok(())
+    }
+
+    // Recognises loop idioms and straight-line arithmetic and lets `run` execute
+    // them as super-instructions. Returns how many were found. Registers,
+    // steps and errors end up exactly as in an unoptimized run; `step` always
+    // executes single instructions.
+    pub fn optimize(&mut self) -> usize {
+        self.fused = optimizer::find_super_instructions(&self.program);
+        self.fused.iter().flatten().count()
+    }
+
+    pub fn clear_optimizations(&mut self) {
+        self.fused.clear();
+    }
+
+    // (pc, super-instruction) pairs found by `optimize`
+    pub fn super_instructions(&self) -> impl Iterator<Item = (usize, &SuperInstruction)> + '_ {
+        self.fused
+            .iter()
+            .enumerate()
+            .filter_map(|(pc, op)| op.as_ref().map(|op| (pc, op)))
+    }
+
+    fn jump(&self, offset: i64) -> Result<usize, VmError> {
+        let target = self.pc as i64 + offset;
+        if target < 0 || target > self.program.len() as i64 {
+            return Err(VmError::JumpOutOfBounds { pc: self.pc, offset });
+        }
+
+        Ok(target as usize)
+    }
+
+    // Executes one instruction. Returns false without doing anything once the
+    // machine has halted.
+    pub fn step(&mut self) -> Result<bool, VmError> {
+        if self.is_halted() {
+            return Ok(false);
+        }
+
+        let overflow = VmError::Overflow { pc: self.pc };
+        let next = match self.program[self.pc] {
+            Instruction::Hlf(reg) => {
+                self.registers[reg] /= 2;
+                self.pc + 1
+            }
+            Instruction::Tpl(reg) => {
+                self.registers[reg] = self.registers[reg].checked_mul(3).ok_or(overflow)?;
+                self.pc + 1
+            }
+            Instruction::Inc(reg) => {
+                self.registers[reg] = self.registers[reg].checked_add(1).ok_or(overflow)?;
+                self.pc + 1
+            }
+            Instruction::Jmp(offset) => self.jump(offset)?,
+            Instruction::Jie(reg, offset) if self.registers[reg].is_multiple_of(2) => self.jump(offset)?,
+            Instruction::Jio(reg, offset) if self.registers[reg] == 1 => self.jump(offset)?,
+            Instruction::Jie(..) | Instruction::Jio(..) => self.pc + 1,
+        };
+
+        self.pc = next;
+        self.steps += 1;
+        Ok(true)
+    }
+
+    // Runs until the program halts or `limit` more instructions have executed
+    pub fn run(&mut self, limit: Option<u64>) -> Result<(), VmError> {
+        let mut executed = 0;
+        while !self.is_halted() {
+            if limit.is_some_and(|limit| executed >= limit) {
+                return Err(VmError::InstructionLimit(executed));
+            }
+
+            if let Some(Some(op)) = self.fused.get(self.pc) {
+                let budget = limit.map_or(u64::MAX, |limit| limit - executed);
+                let (next, steps) = op.apply(&mut self.registers, self.pc, budget);
+                if steps > 0 {
+                    self.pc = next;
+                    self.steps += steps;
+                    executed += steps;
+                    continue;
+                }
+            }
+
+            self.step()?;
+            executed += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_vm_example() {
+    let mut vm = Vm::parse("inc a\njio a, +2\ntpl a\ninc a", 2).unwrap();
+    vm.run(None).unwrap();
+    assert_eq!(vm.registers(), &[2, 0]);
+    assert_eq!(vm.steps(), 3);
+    assert!(vm.is_halted());
+    assert_eq!(vm.step(), Ok(false));
+
+    vm.reset(&[1]).unwrap();
+    vm.run(None).unwrap();
+    assert_eq!(vm.registers(), &[7, 0]);
+
+    assert_eq!(vm.reset(&[1, 2, 3]), Err(VmError::TooManyRegisters { given: 3, available: 2 }));
+    assert_eq!(vm.registers(), &[7, 0]);
+}
+
+#[test]
+fn test_vm_errors() {
+    assert_eq!(Vm::parse("inc c", 2).unwrap_err(), VmError::UnknownRegister("c".to_string()));
+    assert_eq!(Vm::parse("inc c", 3).unwrap().registers().len(), 3);
+    assert!(matches!(Vm::parse("jmp x", 2), Err(VmError::Parse(_))));
+
+    let mut vm = Vm::parse("inc a\njmp -2", 1).unwrap();
+    assert_eq!(vm.run(None), Err(VmError::JumpOutOfBounds { pc: 1, offset: -2 }));
+
+    let mut vm = Vm::parse("inc a\njmp -1", 1).unwrap();
+    assert_eq!(vm.run(Some(1000)), Err(VmError::InstructionLimit(1000)));
+    assert_eq!(vm.register(0), 500);
+}
+
+#[test]
+fn test_instruction_display_round_trip() {
+    let source = "hlf a\ntpl b\ninc a\njmp +7\njie a, +4\njio b, -3";
+    let vm = Vm::parse(source, 2).unwrap();
+    let printed = vm.program().iter().map(|i| i.to_string()).collect::<Vec<String>>();
+    assert_eq!(printed.join("\n"), source);
+}
@@ -0,0 +1,315 @@
+use std::fmt;
+
+use super::{Instruction, Vm, VmError, register_name};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn holds(&self, left: u64, right: u64) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    Pc(usize),
+    Register(usize, Comparison, u64), // register, comparison, value
+}
+
+impl Breakpoint {
+    fn hit(&self, vm: &Vm) -> bool {
+        match self {
+            Breakpoint::Pc(pc) => vm.pc() == *pc,
+            Breakpoint::Register(reg, comparison, value) => comparison.holds(vm.register(*reg), *value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Halted,
+    Breakpoint(usize), // index into the debugger's breakpoints
+    StepLimit,
+}
+
+// Program counter, step count and registers at one moment of a run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub pc: usize,
+    pub steps: u64,
+    pub registers: Vec<u64>,
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "step {:>8}  pc {:>3}", self.steps, self.pc)?;
+        for (reg, value) in self.registers.iter().enumerate() {
+            write!(f, "  {}={}", register_name(reg), value)?;
+        }
+        Ok(())
+    }
+}
+
+// One executed instruction, with the machine state after it ran
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub instruction: Instruction,
+    pub after: Snapshot,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>3}: {:<12} -> {}", self.pc, self.instruction.to_string(), self.after)
+    }
+}
+
+type Tracer = Box<dyn FnMut(&TraceEntry)>;
+
+// Drives a VM one instruction at a time, counting how often each instruction
+// runs and stopping at breakpoints. Breakpoints are checked before an
+// instruction executes, except for the instruction a run stopped on, which the
+// next run executes first so continuing past a breakpoint works.
+pub struct Debugger {
+    vm: Vm,
+    breakpoints: Vec<Breakpoint>,
+    profile: Vec<u64>,
+    tracer: Option<Tracer>,
+    resume_pc: Option<usize>, // where the last run stopped at a breakpoint
+}
+
+impl Debugger {
+    pub fn new(vm: Vm) -> Self {
+        Debugger {
+            profile: vec![0; vm.program().len()],
+            vm,
+            breakpoints: Vec::new(),
+            tracer: None,
+            resume_pc: None,
+        }
+    }
+
+    pub fn vm(&self) -> &Vm {
+        &self.vm
+    }
+
+    pub fn vm_mut(&mut self) -> &mut Vm {
+        &mut self.vm
+    }
+
+    pub fn into_vm(self) -> Vm {
+        self.vm
+    }
+
+    // Returns the index reported by `StopReason::Breakpoint`
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        self.breakpoints.push(breakpoint);
+        self.breakpoints.len() - 1
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    // Called with every executed instruction, e.g. `|entry| println!("{}", entry)`
+    pub fn set_tracer<F: FnMut(&TraceEntry) + 'static>(&mut self, tracer: F) {
+        self.tracer = Some(Box::new(tracer));
+    }
+
+    pub fn clear_tracer(&mut self) {
+        self.tracer = None;
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pc: self.vm.pc(),
+            steps: self.vm.steps(),
+            registers: self.vm.registers().to_vec(),
+        }
+    }
+
+    pub fn step(&mut self) -> Result<bool, VmError> {
+        self.resume_pc = None;
+        let pc = self.vm.pc();
+        if !self.vm.step()? {
+            return Ok(false);
+        }
+
+        self.profile[pc] += 1;
+        if self.tracer.is_some() {
+            let entry = TraceEntry {
+                pc,
+                instruction: self.vm.program()[pc],
+                after: self.snapshot(),
+            };
+            if let Some(tracer) = self.tracer.as_mut() {
+                tracer(&entry);
+            }
+        }
+
+        Ok(true)
+    }
+
+    // Runs until the program halts, a breakpoint is hit or `limit` more
+    // instructions have executed
+    pub fn run(&mut self, limit: Option<u64>) -> Result<StopReason, VmError> {
+        let mut executed = 0;
+        let mut resume_pc = self.resume_pc.take();
+        while !self.vm.is_halted() {
+            let pc = self.vm.pc();
+            if resume_pc.take() != Some(pc)
+                && let Some(index) = self.breakpoints.iter().position(|bp| bp.hit(&self.vm))
+            {
+                self.resume_pc = Some(pc);
+                return Ok(StopReason::Breakpoint(index));
+            }
+
+            if limit.is_some_and(|limit| executed >= limit) {
+                return Ok(StopReason::StepLimit);
+            }
+
+            self.step()?;
+            executed += 1;
+        }
+
+        Ok(StopReason::Halted)
+    }
+
+    // Execution count for every instruction, indexed by pc
+    pub fn profile(&self) -> &[u64] {
+        &self.profile
+    }
+
+    pub fn reset_profile(&mut self) {
+        self.profile.iter_mut().for_each(|count| *count = 0);
+    }
+
+    // The `count` most executed instructions as (pc, executions), busiest first
+    pub fn hot_spots(&self, count: usize) -> Vec<(usize, u64)> {
+        let mut spots = self
+            .profile
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, executions)| executions > 0)
+            .collect::<Vec<(usize, u64)>>();
+        spots.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        spots.truncate(count);
+        spots
+    }
+
+    // Annotated listing of the program with execution counts
+    pub fn profile_report(&self) -> String {
+        let total = self.profile.iter().sum::<u64>().max(1);
+        self.vm
+            .program()
+            .iter()
+            .zip(&self.profile)
+            .enumerate()
+            .map(|(pc, (instruction, &count))| {
+                let percent = count as f64 * 100.0 / total as f64;
+                format!("{:>3}: {:<12} {:>10} {:>6.2}%\n", pc, instruction.to_string(), count, percent)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+const COUNTDOWN: &str = "inc a\ninc a\ninc a\njio a, +3\nhlf a\njmp -2\ninc b";
+
+#[test]
+fn test_debugger_profile_and_trace() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let mut debugger = Debugger::new(Vm::parse(COUNTDOWN, 2).unwrap());
+    let trace = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&trace);
+    debugger.set_tracer(move |entry| sink.borrow_mut().push(entry.to_string()));
+
+    assert_eq!(debugger.run(None), Ok(StopReason::Halted));
+    assert_eq!(debugger.vm().registers(), &[1, 1]);
+    // a goes 3 -> 1 through one halving, so the loop body runs once
+    assert_eq!(debugger.profile(), &[1, 1, 1, 2, 1, 1, 1]);
+    assert_eq!(debugger.hot_spots(2), vec![(3, 2), (0, 1)]);
+    assert_eq!(trace.borrow().len(), 8);
+    assert_eq!(trace.borrow()[0], "  0: inc a        -> step        1  pc   1  a=1  b=0");
+    assert!(debugger.profile_report().lines().any(|line| line == "  3: jio a, +3             2  25.00%"));
+}
+
+#[test]
+fn test_debugger_breakpoints() {
+    let mut debugger = Debugger::new(Vm::parse(COUNTDOWN, 2).unwrap());
+    let at_loop = debugger.add_breakpoint(Breakpoint::Pc(3));
+    assert_eq!(debugger.run(None), Ok(StopReason::Breakpoint(at_loop)));
+    assert_eq!(debugger.snapshot(), Snapshot { pc: 3, steps: 3, registers: vec![3, 0] });
+
+    // Resuming steps over the breakpoint we stopped on
+    assert_eq!(debugger.run(None), Ok(StopReason::Breakpoint(at_loop)));
+    assert_eq!(debugger.snapshot().registers, vec![1, 0]);
+
+    debugger.clear_breakpoints();
+    let a_small = debugger.add_breakpoint(Breakpoint::Register(0, Comparison::Less, 2));
+    assert_eq!(debugger.run(None), Ok(StopReason::Breakpoint(a_small)));
+    assert_eq!(debugger.vm().pc(), 6);
+
+    debugger.clear_breakpoints();
+    assert_eq!(debugger.run(Some(0)), Ok(StopReason::StepLimit));
+    assert_eq!(debugger.run(None), Ok(StopReason::Halted));
+    assert_eq!(debugger.snapshot().to_string(), "step        8  pc   7  a=1  b=1");
+}
+
+#[test]
+fn test_debugger_breakpoints_at_start() {
+    let mut debugger = Debugger::new(Vm::parse(COUNTDOWN, 2).unwrap());
+    let at_start = debugger.add_breakpoint(Breakpoint::Pc(0));
+    assert_eq!(debugger.run(None), Ok(StopReason::Breakpoint(at_start)));
+    assert_eq!(debugger.snapshot(), Snapshot { pc: 0, steps: 0, registers: vec![0, 0] });
+    assert_eq!(debugger.run(None), Ok(StopReason::Halted));
+
+    // A condition that already holds stops a fresh run, and again after a reset
+    let mut debugger = Debugger::new(Vm::parse(COUNTDOWN, 2).unwrap());
+    let b_zero = debugger.add_breakpoint(Breakpoint::Register(1, Comparison::Equal, 0));
+    assert_eq!(debugger.run(None), Ok(StopReason::Breakpoint(b_zero)));
+    assert_eq!(debugger.vm().steps(), 0);
+    assert_eq!(debugger.run(Some(2)), Ok(StopReason::Breakpoint(b_zero)));
+    assert_eq!(debugger.vm().pc(), 1);
+    debugger.vm_mut().reset(&[]).unwrap();
+    debugger.step().unwrap();
+    debugger.vm_mut().reset(&[]).unwrap();
+    assert_eq!(debugger.run(None), Ok(StopReason::Breakpoint(b_zero)));
+    assert_eq!(debugger.vm().steps(), 0);
+}
+
+#[test]
+fn test_debugger_profile_finds_the_collatz_loop() {
+    // The tail of a day23 program: count Collatz steps from a into b
+    let program = "jio a, +8\ninc b\njie a, +4\ntpl a\ninc a\njmp +2\nhlf a\njmp -7";
+    let mut vm = Vm::parse(program, 2).unwrap();
+    vm.reset(&[7]).unwrap();
+    let mut debugger = Debugger::new(vm);
+    assert_eq!(debugger.run(None), Ok(StopReason::Halted));
+    assert_eq!(debugger.vm().register(1), 16);
+
+    // 7 takes 16 steps to reach 1, 5 of them odd
+    assert_eq!(debugger.profile(), &[17, 16, 16, 5, 5, 5, 11, 16]);
+    assert_eq!(debugger.hot_spots(3), vec![(0, 17), (1, 16), (2, 16)]);
+    let report = debugger.profile_report();
+    assert_eq!(report.lines().count(), 8);
+    assert!(report.lines().any(|line| line == "  0: jio a, +8            17  18.68%"), "{report}");
+}
@@ -0,0 +1,474 @@
+use std::fmt;
+
+use super::{Instruction, register_name};
+
+// Replacement for a sequence of instructions starting at one pc. The original
+// instructions stay in the program, a super-instruction only runs when the
+// program counter lands exactly on its first instruction and it can complete
+// without overflowing or exceeding the instruction budget. Otherwise the VM
+// interprets the original instructions, so errors and limits behave as if the
+// program had never been optimized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuperInstruction {
+    // A straight run of `tpl`/`inc`, each touched register becomes r * mul + add
+    Affine {
+        updates: Vec<AffineUpdate>,
+        len: usize,
+    },
+    // while counter != 1 { <affine body>; counter /= 2 } followed by the jump
+    // out of the loop, so the body runs floor(log2(counter)) times and folds
+    // into one multiply and add per register
+    MultiplyLoop {
+        counter: usize,
+        updates: Vec<AffineUpdate>, // one pass of the body
+        body: usize,
+        exit: usize,
+    },
+    // while r != 1 { counter += 1; r = if r is even { r / 2 } else { 3r + 1 } }
+    // followed by the jump out of the loop
+    Collatz {
+        register: usize,
+        counter: usize,
+        exit: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AffineUpdate {
+    pub register: usize,
+    pub mul: u64,
+    pub add: u64,
+}
+
+// Instructions executed by one pass of the Collatz loop body
+const COLLATZ_EVEN_STEPS: u64 = 5; // jio, inc, jie, hlf, jmp
+const COLLATZ_ODD_STEPS: u64 = 7; // jio, inc, jie, tpl, inc, jmp, jmp
+const COLLATZ_LEN: usize = 8;
+
+// Instructions around the body of a multiply loop: jio, hlf, jmp
+const MULTIPLY_LOOP_OVERHEAD: usize = 3;
+
+fn format_updates(updates: &[AffineUpdate]) -> String {
+    updates
+        .iter()
+        .map(|u| format!("{0} = {0} * {1} + {2}", register_name(u.register), u.mul, u.add))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl fmt::Display for SuperInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuperInstruction::Affine { updates, len } => write!(f, "affine[{}] {}", len, format_updates(updates)),
+            SuperInstruction::MultiplyLoop {
+                counter,
+                updates,
+                body,
+                exit,
+            } => write!(
+                f,
+                "multiply-loop[{}] while {} != 1 {}, exit {}",
+                body,
+                register_name(*counter),
+                format_updates(updates),
+                exit
+            ),
+            SuperInstruction::Collatz { register, counter, exit } => write!(
+                f,
+                "collatz {}, count {}, exit {}",
+                register_name(*register),
+                register_name(*counter),
+                exit
+            ),
+        }
+    }
+}
+
+fn match_collatz(program: &[Instruction], pc: usize) -> Option<SuperInstruction> {
+    use Instruction::*;
+
+    let body = program.get(pc..pc + COLLATZ_LEN)?;
+    let [Jio(r, exit), Inc(c), Jie(r1, 4), Tpl(r2), Inc(r3), Jmp(2), Hlf(r4), Jmp(-7)] = *body
+    else {
+        return None;
+    };
+
+    // An exit landing inside the loop would keep it running
+    let exit = pc as i64 + exit;
+    let inside = pc as i64..(pc + COLLATZ_LEN) as i64;
+    if c == r || [r1, r2, r3, r4].iter().any(|&reg| reg != r) || inside.contains(&exit) {
+        return None;
+    }
+    if exit < 0 || exit > program.len() as i64 {
+        return None;
+    }
+
+    Some(SuperInstruction::Collatz {
+        register: r,
+        counter: c,
+        exit: exit as usize,
+    })
+}
+
+// jio c, +exit / tpl and inc on other registers / hlf c / jmp back to the jio
+fn match_multiply_loop(program: &[Instruction], pc: usize) -> Option<SuperInstruction> {
+    let Instruction::Jio(counter, exit) = *program.get(pc)? else {
+        return None;
+    };
+    let Some(SuperInstruction::Affine { updates, len: body }) = match_affine_run(program, pc + 1, 1) else {
+        return None;
+    };
+    let len = body + MULTIPLY_LOOP_OVERHEAD;
+    let tail = program.get(pc + 1 + body..pc + len)?;
+    if tail != [Instruction::Hlf(counter), Instruction::Jmp(1 - len as i64)]
+        || updates.iter().any(|u| u.register == counter)
+    {
+        return None;
+    }
+
+    let exit = pc as i64 + exit;
+    if (pc as i64..(pc + len) as i64).contains(&exit) || exit < 0 || exit > program.len() as i64 {
+        return None;
+    }
+
+    Some(SuperInstruction::MultiplyLoop {
+        counter,
+        updates,
+        body,
+        exit: exit as usize,
+    })
+}
+
+fn match_affine(program: &[Instruction], pc: usize) -> Option<SuperInstruction> {
+    match_affine_run(program, pc, 2)
+}
+
+fn match_affine_run(program: &[Instruction], pc: usize, min_len: usize) -> Option<SuperInstruction> {
+    let mut updates: Vec<AffineUpdate> = Vec::new();
+    let mut len = 0;
+    for instruction in &program[pc..] {
+        let (register, tripled) = match *instruction {
+            Instruction::Inc(reg) => (reg, false),
+            Instruction::Tpl(reg) => (reg, true),
+            _ => break,
+        };
+
+        let index = match updates.iter().position(|u| u.register == register) {
+            Some(index) => index,
+            None => {
+                updates.push(AffineUpdate { register, mul: 1, add: 0 });
+                updates.len() - 1
+            }
+        };
+        let current = updates[index];
+        let next = if tripled {
+            current.mul.checked_mul(3).zip(current.add.checked_mul(3))
+        }
+        else {
+            current.add.checked_add(1).map(|add| (current.mul, add))
+        };
+        let Some((mul, add)) = next else {
+            break;
+        };
+
+        updates[index] = AffineUpdate { register, mul, add };
+        len += 1;
+    }
+
+    (len >= min_len).then_some(SuperInstruction::Affine { updates, len })
+}
+
+// Super-instruction for every pc that starts a recognised idiom
+pub fn find_super_instructions(program: &[Instruction]) -> Vec<Option<SuperInstruction>> {
+    (0..program.len())
+        .map(|pc| {
+            match_collatz(program, pc)
+                .or_else(|| match_multiply_loop(program, pc))
+                .or_else(|| match_affine(program, pc))
+        })
+        .collect()
+}
+
+impl SuperInstruction {
+    // Runs the super-instruction from `pc` using at most `budget` steps.
+    // Returns the new pc and the number of original instructions accounted
+    // for; zero steps means the caller has to interpret the instruction at
+    // `pc` itself.
+    pub(super) fn apply(&self, registers: &mut [u64], pc: usize, budget: u64) -> (usize, u64) {
+        match self {
+            SuperInstruction::Affine { updates, len } => {
+                if (*len as u64) > budget {
+                    return (pc, 0);
+                }
+
+                let results = updates
+                    .iter()
+                    .map(|u| registers[u.register].checked_mul(u.mul)?.checked_add(u.add))
+                    .collect::<Option<Vec<u64>>>();
+                let Some(results) = results else {
+                    return (pc, 0);
+                };
+
+                for (update, value) in updates.iter().zip(results) {
+                    registers[update.register] = value;
+                }
+                (pc + len, *len as u64)
+            }
+            SuperInstruction::MultiplyLoop {
+                counter,
+                updates,
+                body,
+                exit,
+            } => {
+                // Zero never reaches one, leave the endless loop to the
+                // interpreter so instruction limits still apply
+                let value = registers[*counter];
+                if value == 0 {
+                    return (pc, 0);
+                }
+
+                let passes = value.ilog2() as u64;
+                let steps = (passes * (*body + MULTIPLY_LOOP_OVERHEAD) as u64).checked_add(1);
+                if steps.is_none_or(|steps| steps > budget) {
+                    return (pc, 0);
+                }
+
+                // Every pass only grows the registers, so if the final values
+                // fit no pass in between overflowed either
+                let results = updates
+                    .iter()
+                    .map(|u| {
+                        let (mut mul, mut add) = (1u64, 0u64);
+                        for _ in 0..passes {
+                            (mul, add) = (mul.checked_mul(u.mul)?, add.checked_mul(u.mul)?.checked_add(u.add)?);
+                        }
+                        registers[u.register].checked_mul(mul)?.checked_add(add)
+                    })
+                    .collect::<Option<Vec<u64>>>();
+                let (Some(results), Some(steps)) = (results, steps) else {
+                    return (pc, 0);
+                };
+
+                for (update, value) in updates.iter().zip(results) {
+                    registers[update.register] = value;
+                }
+                registers[*counter] = 1;
+                (*exit, steps)
+            }
+            SuperInstruction::Collatz { register, counter, exit } => {
+                let mut value = registers[*register];
+                let mut count = registers[*counter];
+                let mut steps = 0;
+                let mut next_pc = pc;
+                loop {
+                    if value == 1 {
+                        if steps < budget {
+                            steps += 1;
+                            next_pc = *exit;
+                        }
+                        break;
+                    }
+
+                    // Zero never reaches one, leave the endless loop to the
+                    // interpreter so instruction limits still apply
+                    if value == 0 {
+                        break;
+                    }
+
+                    let next = if value.is_multiple_of(2) {
+                        Some((value / 2, COLLATZ_EVEN_STEPS))
+                    }
+                    else {
+                        value.checked_mul(3).and_then(|v| v.checked_add(1)).map(|v| (v, COLLATZ_ODD_STEPS))
+                    };
+                    let (Some((next, cost)), Some(next_count)) = (next, count.checked_add(1)) else {
+                        break;
+                    };
+                    if budget - steps < cost {
+                        break;
+                    }
+
+                    value = next;
+                    count = next_count;
+                    steps += cost;
+                }
+
+                registers[*register] = value;
+                registers[*counter] = count;
+                (next_pc, steps)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_find_super_instructions() {
+    let program = crate::vm::Vm::parse(
+        "inc a\ntpl a\ninc a\ninc b\njmp +1\njio a, +8\ninc b\njie a, +4\ntpl a\ninc a\njmp +2\nhlf a\njmp -7",
+        2,
+    )
+    .unwrap();
+    let found = find_super_instructions(program.program());
+    assert_eq!(
+        found[0],
+        Some(SuperInstruction::Affine {
+            updates: vec![
+                AffineUpdate { register: 0, mul: 3, add: 4 },
+                AffineUpdate { register: 1, mul: 1, add: 1 },
+            ],
+            len: 4,
+        })
+    );
+    assert_eq!(found[0].as_ref().unwrap().to_string(), "affine[4] a = a * 3 + 4, b = b * 1 + 1");
+    assert_eq!(found[3], None);
+    assert_eq!(
+        found[5],
+        Some(SuperInstruction::Collatz { register: 0, counter: 1, exit: 13 })
+    );
+    assert_eq!(found[8], Some(SuperInstruction::Affine {
+        updates: vec![AffineUpdate { register: 0, mul: 3, add: 1 }],
+        len: 2,
+    }));
+}
+
+#[test]
+fn test_find_multiply_loop() {
+    let program = crate::vm::Vm::parse("inc b\njio c, +6\ninc a\ntpl b\ninc a\nhlf c\njmp -5\ninc b", 3).unwrap();
+    let found = find_super_instructions(program.program());
+    assert_eq!(
+        found[1],
+        Some(SuperInstruction::MultiplyLoop {
+            counter: 2,
+            updates: vec![
+                AffineUpdate { register: 0, mul: 1, add: 2 },
+                AffineUpdate { register: 1, mul: 3, add: 0 },
+            ],
+            body: 3,
+            exit: 7,
+        })
+    );
+    assert_eq!(
+        found[1].as_ref().unwrap().to_string(),
+        "multiply-loop[3] while c != 1 a = a * 1 + 2, b = b * 3 + 0, exit 7"
+    );
+
+    // 13 halves to 1 in three passes of six instructions, plus the exit
+    let mut registers = [5, 2, 13];
+    assert_eq!(found[1].as_ref().unwrap().apply(&mut registers, 1, u64::MAX), (7, 19));
+    assert_eq!(registers, [11, 54, 1]);
+    assert_eq!(found[1].as_ref().unwrap().apply(&mut registers, 1, 0), (1, 0));
+
+    // The body may not touch the counter and the jump has to close the loop
+    let broken = [
+        "jio c, +4\ninc c\nhlf c\njmp -3",
+        "jio c, +4\ninc a\nhlf c\njmp -2",
+        "jio c, +2\ninc a\nhlf c\njmp -3",
+    ];
+    for source in broken {
+        let program = crate::vm::Vm::parse(source, 3).unwrap();
+        assert_eq!(match_multiply_loop(program.program(), 0), None, "{source}");
+    }
+}
+
+#[test]
+fn test_collatz_exit_must_leave_loop() {
+    let program = crate::vm::Vm::parse("jio a, +3\ninc b\njie a, +4\ntpl a\ninc a\njmp +2\nhlf a\njmp -7", 2).unwrap();
+    assert_eq!(match_collatz(program.program(), 0), None);
+}
+
+#[cfg(test)]
+const COLLATZ_PROGRAM: &str = "jio a, +16\ninc a\ntpl a\ntpl a\ninc a\ntpl a\njmp +8\ntpl a\ninc a\ninc a\ntpl a\n\
+    inc a\ntpl a\ninc a\ninc a\ntpl a\njio a, +8\ninc b\njie a, +4\ntpl a\ninc a\njmp +2\nhlf a\njmp -7";
+
+#[cfg(test)]
+fn assert_same_run(source: &str, num_registers: usize, start: &[u64], limit: Option<u64>) {
+    let mut plain = crate::vm::Vm::parse(source, num_registers).unwrap();
+    plain.reset(start).unwrap();
+    let mut optimized = plain.clone();
+    optimized.optimize();
+
+    let expected = plain.run(limit);
+    assert_eq!(optimized.run(limit), expected, "{source:?} from {start:?}");
+    assert_eq!(optimized.registers(), plain.registers(), "{source:?} from {start:?}");
+    assert_eq!(optimized.pc(), plain.pc());
+    assert_eq!(optimized.steps(), plain.steps());
+}
+
+#[test]
+fn test_optimized_collatz_matches_interpreter() {
+    for a in 0..50 {
+        assert_same_run(COLLATZ_PROGRAM, 2, &[a, 0], None);
+    }
+    for limit in 0..120 {
+        assert_same_run(COLLATZ_PROGRAM, 2, &[0, 0], Some(limit));
+        assert_same_run(COLLATZ_PROGRAM, 2, &[1, 0], Some(limit));
+    }
+
+    // The counter reaching u64::MAX and 3a + 1 overflowing both surface as
+    // the interpreter's overflow errors
+    assert_same_run(COLLATZ_PROGRAM, 2, &[6, u64::MAX - 3], None);
+    assert_same_run(COLLATZ_PROGRAM, 2, &[u64::MAX / 2, 0], None);
+    assert_same_run(COLLATZ_PROGRAM, 2, &[0, 0], Some(10_000));
+}
+
+#[test]
+fn test_optimized_affine_matches_interpreter() {
+    let source = "tpl a\ninc a\ninc b\ntpl a\ntpl b\ninc a";
+    for start in [[0, 0], [5, 7], [u64::MAX / 9 - 1, 0], [u64::MAX / 9, 0], [1, u64::MAX / 3]] {
+        for limit in [None, Some(3), Some(6)] {
+            assert_same_run(source, 2, &start, limit);
+        }
+    }
+}
+
+#[test]
+fn test_optimized_multiply_loop_matches_interpreter() {
+    // a += 2 * floor(log2(c)) and b *= 3 that often, then one more inc b
+    let source = "inc b\njio c, +6\ninc a\ntpl b\ninc a\nhlf c\njmp -5\ninc b";
+    for c in 1..70 {
+        assert_same_run(source, 3, &[c, 0, c], None);
+    }
+    for limit in 0..40 {
+        assert_same_run(source, 3, &[0, 0, 13], Some(limit));
+        assert_same_run(source, 3, &[0, 0, 0], Some(limit));
+    }
+
+    // Overflow part way through the loop surfaces as the interpreter's error
+    assert_same_run(source, 3, &[0, u64::MAX / 3, 4], None);
+    assert_same_run(source, 3, &[u64::MAX - 4, 0, 4], None);
+    assert_same_run(source, 3, &[u64::MAX - 5, 0, 4], None);
+    assert_same_run(source, 3, &[0, 0, u64::MAX], None);
+    assert_same_run(source, 3, &[0, 0, 0], Some(10_000));
+}
+
+#[test]
+fn test_optimized_random_programs_match_interpreter() {
+    // Small linear congruential generator so the programs are reproducible
+    let mut seed = 0x2015_0023_u64;
+    let mut next = |bound: u64| {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (seed >> 33) % bound
+    };
+
+    let collatz = "jio a, +8\ninc b\njie a, +4\ntpl a\ninc a\njmp +2\nhlf a\njmp -7";
+    let multiply = "jio b, +5\ninc a\ninc a\nhlf b\njmp -4";
+    for _ in 0..300 {
+        let mut lines = Vec::new();
+        while lines.len() < 20 {
+            let reg = if next(3) == 0 { 'b' } else { 'a' };
+            match next(9) {
+                0 | 1 => lines.push(format!("inc {reg}")),
+                2 | 3 => lines.push(format!("tpl {reg}")),
+                4 => lines.push(format!("hlf {reg}")),
+                5 => lines.push(format!("jie {reg}, {:+}", next(7) as i64 - 3)),
+                6 => lines.push(format!("jio {reg}, {:+}", next(7) as i64 - 3)),
+                7 => lines.extend(multiply.lines().map(str::to_string)),
+                _ => lines.extend(collatz.lines().map(str::to_string)),
+            }
+        }
+
+        let source = lines.join("\n");
+        let start = [next(20), next(20)];
+        assert_same_run(&source, 2, &start, Some(next(5000)));
+    }
+}